@@ -8,18 +8,19 @@ async fn main() {
         .one_day(
             &OneDayArgs::builder()
                 .year(now.year() as _)
-                .month(now.month().into())
+                .month_typed(now.month())
                 .day(now.day())
                 .tz(0.0)
                 .lat(43.9033)
                 .long(-91.6401)
-                .build(),
+                .build()
+                .unwrap(),
         )
         .await
         .unwrap();
     println!("{data:#?}");
     let current_year = client
-        .phases(&PhaseArgs::year(now.year() as _))
+        .phases(&PhaseArgs::year_checked(now.year() as _).unwrap())
         .await
         .unwrap();
     println!("{current_year:#?}");
@@ -27,7 +28,7 @@ async fn main() {
         .phases(
             &PhaseArgs::build_by_date()
                 .day(now.day())
-                .month(now.month().into())
+                .month_typed(now.month())
                 .year(now.year() as _)
                 .count(10)
                 .build()
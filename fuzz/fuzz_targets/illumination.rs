@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use moon_unit::Illumination;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Illumination>(data);
+    if let Ok(percent) = std::str::from_utf8(data) {
+        let _ = serde_json::from_value::<Illumination>(serde_json::Value::from(percent));
+    }
+});
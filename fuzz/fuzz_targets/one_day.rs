@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use moon_unit::OneDay;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(one_day) = serde_json::from_slice::<OneDay>(data) {
+        let data = one_day.properties.data;
+        let _ = data.when();
+        let _ = data.moon_age();
+        let _ = data.closest_phase.when();
+        for event in data.sun_data.iter().chain(&data.moon_data) {
+            let _ = event.when();
+        }
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use moon_unit::CelestialEvent;
+
+fuzz_target!(|data: &[u8]| {
+    // raw bytes, mostly exercising the JSON parser around the visitor
+    if let Ok(event) = serde_json::from_slice::<CelestialEvent>(data) {
+        let _ = event.when();
+    }
+    // arbitrary strings in the time position to hit the visitor directly
+    if let Ok(time) = std::str::from_utf8(data) {
+        let json = serde_json::json!({ "phen": "Rise", "time": time });
+        if let Ok(event) = serde_json::from_value::<CelestialEvent>(json) {
+            let _ = event.when();
+        }
+    }
+});
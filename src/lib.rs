@@ -1,5 +1,11 @@
-use std::ops::Rem;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::RangeInclusive,
+    sync::Mutex,
+};
 
+use anyhow::Context;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Deserializer, Serialize};
 use time::{Date, OffsetDateTime, PrimitiveDateTime};
 
@@ -8,370 +14,6808 @@ type Result<T = (), E = anyhow::Error> = core::result::Result<T, E>;
 pub struct Client {
     inner: reqwest::Client,
     base_url: String,
+    headers: reqwest::header::HeaderMap,
+    validators: Option<Mutex<ConditionalCache>>,
+    in_flight: Option<Mutex<HashMap<String, InFlight>>>,
+    fractional_tz_correction: bool,
+    http_version: HttpVersion,
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "disk-cache")]
+    disk_cache: Option<std::path::PathBuf>,
+    request_interceptors: Vec<Interceptor<reqwest::Request>>,
+    response_interceptors: Vec<Interceptor<reqwest::Response>>,
+    clock: Box<dyn Clock>,
+    metrics: Option<Box<dyn Metrics>>,
 }
+
+/// The header [`Client::one_day_with_correlation_id`] sends the id in
+pub const CORRELATION_ID_HEADER: &str = "X-Correlation-ID";
+
+/// A callback that observes each outgoing request or incoming response
+type Interceptor<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+/// A response body, `from_cache` is `true` when the server answered
+/// `304 Not Modified` and the body came from the conditional cache
+///
+/// `latency` is only set for the caller that sent the request, it reports the
+/// request to [`Metrics`] once the body has been decoded.
+struct Fetched {
+    body: bytes::Bytes,
+    from_cache: bool,
+    latency: Option<std::time::Duration>,
+}
+
+/// The shared outcome of a request every concurrent caller is waiting on
+type InFlight = std::sync::Arc<tokio::sync::OnceCell<Result<(bytes::Bytes, bool), String>>>;
+
+/// A source of the current time, [`Client`] reads "now" through this so tests
+/// can pin it with a [`FixedClock`]
+pub trait Clock: Send + Sync {
+    fn now_utc(&self) -> OffsetDateTime;
+}
+
+/// The system's clock, the default for a [`Client`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// A clock that always reads the same instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub OffsetDateTime);
+
+impl Clock for FixedClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        self.0
+    }
+}
+
+/// Hooks a [`Client`] calls for every API request, implement this against
+/// your metrics library and install it with [`Client::with_metrics`]
+///
+/// `endpoint` is the API path, e.g. `/api/rstt/oneday`. Every method does
+/// nothing by default.
+pub trait Metrics: Send + Sync {
+    /// A request is about to be sent
+    fn on_request(&self, endpoint: &str) {
+        let _ = endpoint;
+    }
+    /// A response body was received and decoded, `latency` covers sending
+    /// the request through reading the body
+    fn on_success(&self, endpoint: &str, latency: std::time::Duration) {
+        let _ = (endpoint, latency);
+    }
+    /// The request failed
+    fn on_error(&self, endpoint: &str, kind: RequestErrorKind) {
+        let _ = (endpoint, kind);
+    }
+}
+
+/// Why a request failed, see [`Metrics::on_error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestErrorKind {
+    /// The request couldn't be sent or no response arrived
+    Send,
+    /// The server responded with an error status
+    Status(u16),
+    /// The response body couldn't be read
+    Body,
+    /// The response body couldn't be decoded
+    Decode,
+}
+
+/// Which HTTP version a [`Client`] speaks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Let `reqwest` negotiate the version with the server
+    #[default]
+    Negotiate,
+    /// Only use HTTP/1.1, for networks where HTTP/2 to the API fails
+    Http1Only,
+    /// Use HTTP/2 without negotiating first
+    Http2PriorKnowledge,
+}
+
+/// How a [`Client`] handles `3xx` redirects, the default follows up to 10
+/// like `reqwest` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Follow up to this many redirects, a longer chain fails the request
+    Limit(usize),
+    /// Don't follow redirects, a redirect fails the request
+    Never,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Limit(10)
+    }
+}
+
+/// A previously fetched body and the validators the server sent with it
+#[derive(Debug, Clone)]
+struct CachedBody {
+    etag: Option<reqwest::header::HeaderValue>,
+    last_modified: Option<reqwest::header::HeaderValue>,
+    body: bytes::Bytes,
+}
+
+/// The bodies remembered by [`Client::with_conditional_cache`] keyed by URL
+/// and per-call headers (see [`conditional_cache_key`]), the least recently
+/// used is dropped once `capacity` is reached
+#[derive(Debug)]
+struct ConditionalCache {
+    entries: HashMap<String, CachedBody>,
+    /// Keys from least to most recently used
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl ConditionalCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<CachedBody> {
+        let cached = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(cached)
+    }
+
+    fn insert(&mut self, key: String, body: CachedBody) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), body).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(index) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(index) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// The [`ConditionalCache`] key for a request to `url` with the per-call
+/// `headers`, which can change the response (e.g. `Accept-Language`). The
+/// correlation id is left out since it's unique to each call.
+fn conditional_cache_key(
+    url: &reqwest::Url,
+    headers: Option<&reqwest::header::HeaderMap>,
+) -> String {
+    let mut headers: Vec<_> = headers
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| *name != CORRELATION_ID_HEADER)
+        .map(|(name, value)| format!(" {name}: {value:?}"))
+        .collect();
+    headers.sort();
+    let mut key = url.to_string();
+    key.extend(headers);
+    key
+}
+
 const DEFAULT_BASE_URL: &str = "https://aa.usno.navy.mil";
+/// How many responses [`Client::with_conditional_cache`] remembers
+const CONDITIONAL_CACHE_CAPACITY: usize = 256;
+/// The maximum number of requests a stream will have in flight at once
+const MAX_IN_FLIGHT: usize = 4;
+/// The mean length of a synodic month (new moon to new moon) in days
+const SYNODIC_MONTH_DAYS: f32 = 29.530_588;
 
 impl Default for Client {
     fn default() -> Self {
-        Self::new(reqwest::Client::default(), DEFAULT_BASE_URL)
+        Self::from_parts(reqwest::Client::default(), DEFAULT_BASE_URL)
     }
 }
 
 impl From<reqwest::Client> for Client {
     fn from(value: reqwest::Client) -> Self {
-        Self::new(value, DEFAULT_BASE_URL)
+        Self::from_parts(value, DEFAULT_BASE_URL)
     }
 }
 
+#[bon::bon]
 impl Client {
-    pub fn with_base_url(base_url: impl ToString) -> Self {
+    pub fn with_base_url(base_url: impl ToString) -> Result<Self> {
         Self::new(Default::default(), base_url)
     }
 
-    pub fn new(client: reqwest::Client, base_url: impl ToString) -> Self {
+    /// Fails if `base_url` isn't an absolute `http` or `https` url with a
+    /// host, or if it has a query or fragment. A trailing slash is removed.
+    pub fn new(client: reqwest::Client, base_url: impl ToString) -> Result<Self> {
+        let base_url = base_url.to_string();
+        let parsed = reqwest::Url::parse(&base_url)
+            .with_context(|| format!("invalid base url {base_url:?}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") || !parsed.has_host() {
+            anyhow::bail!("invalid base url {base_url:?}, expected an http(s) url with a host")
+        }
+        if parsed.query().is_some() || parsed.fragment().is_some() {
+            anyhow::bail!("invalid base url {base_url:?}, it can't have a query or fragment")
+        }
+        Ok(Self::from_parts(client, base_url.trim_end_matches('/')))
+    }
+
+    fn from_parts(client: reqwest::Client, base_url: &str) -> Self {
         Self {
             inner: client,
             base_url: base_url.to_string(),
+            headers: Default::default(),
+            validators: None,
+            in_flight: None,
+            fractional_tz_correction: false,
+            http_version: HttpVersion::Negotiate,
+            redirect_policy: RedirectPolicy::default(),
+            #[cfg(feature = "disk-cache")]
+            disk_cache: None,
+            request_interceptors: Vec::new(),
+            response_interceptors: Vec::new(),
+            clock: Box::new(SystemClock),
+            metrics: None,
+        }
+    }
+
+    /// Read the current time from `clock` instead of the system clock, this
+    /// affects [`Client::moon_phase_today`], `fetched_at` and which responses
+    /// the disk cache considers settled
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Report request counts, latencies and errors to `metrics`
+    pub fn with_metrics(mut self, metrics: impl Metrics + 'static) -> Self {
+        self.metrics = Some(Box::new(metrics));
+        self
+    }
+
+    fn record(&self, f: impl FnOnce(&dyn Metrics)) {
+        if let Some(metrics) = &self.metrics {
+            f(metrics.as_ref());
+        }
+    }
+
+    /// Report the outcome of decoding a body fetched from `path`, a request
+    /// only counts as a success once its body decodes
+    fn decoded<T>(
+        &self,
+        path: &str,
+        latency: Option<std::time::Duration>,
+        result: Result<T>,
+    ) -> Result<T> {
+        if let Some(latency) = latency {
+            self.record(|metrics| match &result {
+                Ok(_) => metrics.on_success(path, latency),
+                Err(_) => metrics.on_error(path, RequestErrorKind::Decode),
+            });
+        }
+        result
+    }
+
+    /// Call `interceptor` with every request just before it is sent, the
+    /// request can be inspected but not changed and is always sent
+    pub fn with_interceptor(
+        mut self,
+        interceptor: impl Fn(&reqwest::Request) + Send + Sync + 'static,
+    ) -> Self {
+        self.request_interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Call `interceptor` with every response as soon as it arrives, before
+    /// its status is checked or its body is read
+    pub fn with_response_interceptor(
+        mut self,
+        interceptor: impl Fn(&reqwest::Response) + Send + Sync + 'static,
+    ) -> Self {
+        self.response_interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Store phase responses as JSON files under `dir`, keyed by the request
+    /// arguments, and serve repeat requests from there. Only responses whose
+    /// phases are all before today (UTC) are stored since those can't change.
+    /// Unreadable or corrupt files are ignored and replaced by a fresh
+    /// request, failing to write the cache doesn't fail the request.
+    #[cfg(feature = "disk-cache")]
+    pub fn with_disk_cache(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.disk_cache = Some(dir.into());
+        self
+    }
+
+    /// Select the HTTP version to use, this replaces the underlying
+    /// `reqwest::Client` so any configuration on a client passed to
+    /// [`Client::new`] is lost.
+    pub fn with_http_version(mut self, version: HttpVersion) -> Result<Self> {
+        self.http_version = version;
+        self.rebuild()?;
+        Ok(self)
+    }
+
+    /// Choose how redirects are followed, like [`Client::with_http_version`]
+    /// this replaces the underlying `reqwest::Client`
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Result<Self> {
+        self.redirect_policy = policy;
+        self.rebuild()?;
+        Ok(self)
+    }
+
+    /// Rebuild the underlying `reqwest::Client` from this client's settings
+    fn rebuild(&mut self) -> Result {
+        let builder = reqwest::Client::builder();
+        let builder = match self.http_version {
+            HttpVersion::Negotiate => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+        let builder = builder.redirect(match self.redirect_policy {
+            RedirectPolicy::Limit(hops) => reqwest::redirect::Policy::custom(move |attempt| {
+                // `previous` holds every url requested so far, the first of
+                // which wasn't a redirect
+                if attempt.previous().len() > hops {
+                    attempt.error("too many redirects")
+                } else {
+                    attempt.follow()
+                }
+            }),
+            RedirectPolicy::Never => reqwest::redirect::Policy::none(),
+        });
+        #[cfg(feature = "compression")]
+        let builder = builder.gzip(true).brotli(true);
+        self.inner = builder.build().context("failed to build http client")?;
+        Ok(())
+    }
+
+    /// Remember the `ETag`/`Last-Modified` of each response and revalidate
+    /// repeat requests with `If-None-Match`/`If-Modified-Since`, a
+    /// `304 Not Modified` is answered with the remembered body. Responses
+    /// served this way report `true` from `from_cache`.
+    ///
+    /// Every distinct URL (date, location and query) gets an entry holding
+    /// its whole body, up to 256 of them before the least recently used is
+    /// dropped. See [`Client::with_conditional_cache_capacity`] to pick the
+    /// bound and [`Client::clear_conditional_cache`] to empty it.
+    ///
+    /// Per-call headers (e.g. [`Client::one_day_with_headers`]) are part of
+    /// an entry's key, other than the [`CORRELATION_ID_HEADER`], so a call
+    /// with a different `Accept-Language` isn't answered with another's body.
+    pub fn with_conditional_cache(self) -> Self {
+        self.with_conditional_cache_capacity(CONDITIONAL_CACHE_CAPACITY)
+    }
+
+    /// [`Client::with_conditional_cache`] remembering at most `capacity`
+    /// responses
+    pub fn with_conditional_cache_capacity(mut self, capacity: usize) -> Self {
+        self.validators = Some(Mutex::new(ConditionalCache::new(capacity)));
+        self
+    }
+
+    /// Forget every response remembered by the conditional cache, a no-op
+    /// if it isn't enabled
+    pub fn clear_conditional_cache(&self) {
+        if let Some(validators) = &self.validators {
+            validators.lock().unwrap().clear();
         }
     }
 
+    /// Coalesce concurrent identical requests, while a request is in flight
+    /// any other call with the same URL and query waits for it and receives a
+    /// copy of its body instead of sending its own. The caller that sent the
+    /// request gets its original error, the waiting callers get an error with
+    /// the same message (including its causes) that can't be downcast.
+    pub fn with_single_flight(mut self) -> Self {
+        self.in_flight = Some(Mutex::default());
+        self
+    }
+
+    /// Shift the event times of `oneday` responses by the fractional part of
+    /// the requested `tz` the server didn't apply, see
+    /// [`OneDayData::apply_fractional_tz`]
+    ///
+    /// Only enable this after confirming the server drops the fraction for
+    /// your zones (e.g. sunrise for a `+05:30` request is 30 minutes off),
+    /// if the server honors the fraction this makes the times wrong instead.
+    pub fn with_fractional_tz_correction(mut self) -> Self {
+        self.fractional_tz_correction = true;
+        self
+    }
+
+    /// Attach a header to every request this client sends (e.g.
+    /// `Accept-Language` or a tracing correlation id), fails if the name or
+    /// value isn't a valid header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow::anyhow!("invalid header name {name:?}: {e}"))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| anyhow::anyhow!("invalid header value {value:?}: {e}"))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
     pub async fn one_day(&self, query: &OneDayArgs) -> Result<OneDay> {
-        self.inner
-            .get(format!("{}/api/rstt/oneday", self.base_url))
-            .query(query)
-            .send()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to send request: {e}"))?
-            .error_for_status()
-            .map_err(|e| anyhow::anyhow!("invalid status in response: {e}"))?
-            .json()
+        self.one_day_via(&self.base_url, query).await
+    }
+
+    /// [`Client::one_day`] against `base_url` instead of the client's base url,
+    /// useful for failing over to a mirror
+    pub async fn one_day_via(&self, base_url: &str, query: &OneDayArgs) -> Result<OneDay> {
+        self.one_day_inner(base_url, query, None).await
+    }
+
+    /// [`Client::one_day`] with `headers` added to this request only, they
+    /// take precedence over client-wide headers of the same name (see
+    /// [`Client::with_header`])
+    pub async fn one_day_with_headers(
+        &self,
+        query: &OneDayArgs,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Result<OneDay> {
+        self.one_day_inner(&self.base_url, query, Some(headers))
             .await
-            .map_err(|e| anyhow::anyhow!("failed to deserialize response: {e}"))
     }
 
+    /// [`Client::one_day`] with `correlation_id` sent in the
+    /// [`CORRELATION_ID_HEADER`] header and included in the error if the
+    /// request fails, to tie failures to server logs and tracing spans
+    pub async fn one_day_with_correlation_id(
+        &self,
+        query: &OneDayArgs,
+        correlation_id: &str,
+    ) -> Result<OneDay> {
+        async {
+            let mut headers = reqwest::header::HeaderMap::new();
+            let value = reqwest::header::HeaderValue::from_str(correlation_id)
+                .context("invalid correlation id")?;
+            headers.insert(CORRELATION_ID_HEADER, value);
+            self.one_day_inner(&self.base_url, query, Some(&headers))
+                .await
+        }
+        .await
+        .with_context(|| format!("request with correlation id {correlation_id} failed"))
+    }
+
+    async fn one_day_inner(
+        &self,
+        base_url: &str,
+        query: &OneDayArgs,
+        headers: Option<&reqwest::header::HeaderMap>,
+    ) -> Result<OneDay> {
+        let path = "/api/rstt/oneday";
+        let fetched = self.get(base_url, path, query, headers).await?;
+        let mut one_day: OneDay = self.decoded(
+            path,
+            fetched.latency,
+            serde_json::from_slice(&fetched.body).context("failed to deserialize response"),
+        )?;
+        one_day.from_cache = fetched.from_cache;
+        one_day.fetched_at = Some(self.clock.now_utc());
+        one_day.args = Some(query.clone());
+        if self.fractional_tz_correction {
+            one_day.properties.data.apply_fractional_tz(query.tz)?;
+        }
+        Ok(one_day)
+    }
+
+    /// The raw body of a `oneday` response, the status is still checked but
+    /// the body isn't parsed
+    pub async fn one_day_bytes(&self, query: &OneDayArgs) -> Result<bytes::Bytes> {
+        let path = "/api/rstt/oneday";
+        let fetched = self.get(&self.base_url, path, query, None).await?;
+        self.decoded(path, fetched.latency, Ok(fetched.body))
+    }
+
+    /// The phases in the response are guaranteed to be sorted ascending by
+    /// date and time with no duplicate entries.
     pub async fn phases(&self, query: &PhaseArgs) -> Result<MoonPhasesResponse> {
-        let path = if matches!(query, PhaseArgs::Year { .. }) {
-            "year"
-        } else {
-            "date"
-        };
+        self.phases_via(&self.base_url, query).await
+    }
+
+    /// [`Client::phases`] against `base_url` instead of the client's base url,
+    /// useful for failing over to a mirror
+    pub async fn phases_via(
+        &self,
+        base_url: &str,
+        query: &PhaseArgs,
+    ) -> Result<MoonPhasesResponse> {
+        self.phases_inner(base_url, query, None).await
+    }
+
+    /// [`Client::phases`] with `headers` added to this request only, they
+    /// take precedence over client-wide headers of the same name. The disk
+    /// cache, when enabled, is still consulted first.
+    pub async fn phases_with_headers(
+        &self,
+        query: &PhaseArgs,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Result<MoonPhasesResponse> {
+        self.phases_inner(&self.base_url, query, Some(headers))
+            .await
+    }
+
+    async fn phases_inner(
+        &self,
+        base_url: &str,
+        query: &PhaseArgs,
+        headers: Option<&reqwest::header::HeaderMap>,
+    ) -> Result<MoonPhasesResponse> {
+        #[cfg(feature = "disk-cache")]
+        let cache_path = self
+            .disk_cache
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", query.cache_key())));
+        #[cfg(feature = "disk-cache")]
+        if let Some(path) = &cache_path {
+            let cached = tokio::fs::read(path)
+                .await
+                .ok()
+                .and_then(|body| serde_json::from_slice::<MoonPhasesResponse>(&body).ok());
+            if let Some(mut phases) = cached {
+                phases.normalize();
+                phases.from_cache = true;
+                phases.fetched_at = tokio::fs::metadata(path)
+                    .await
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .map(OffsetDateTime::from);
+                return Ok(phases);
+            }
+        }
+        let fetched = self.get(base_url, query.path(), query, headers).await?;
+        let mut phases: MoonPhasesResponse = self.decoded(
+            query.path(),
+            fetched.latency,
+            serde_json::from_slice(&fetched.body).context("failed to deserialize response"),
+        )?;
+        if let PhaseArgs::ByDate { tz: Some(tz), .. } = query {
+            phases.tz.get_or_insert(*tz);
+        }
+        phases.normalize();
+        phases.from_cache = fetched.from_cache;
+        phases.fetched_at = Some(self.clock.now_utc());
+        #[cfg(feature = "disk-cache")]
+        if let Some(path) = &cache_path {
+            if phases.settled_before(self.clock.now_utc().date()) {
+                let _ = write_disk_cache(path, &phases).await;
+            }
+        }
+        Ok(phases)
+    }
+
+    /// The raw body of a phases response, the status is still checked but
+    /// the body isn't parsed
+    pub async fn phases_bytes(&self, query: &PhaseArgs) -> Result<bytes::Bytes> {
+        let fetched = self.get(&self.base_url, query.path(), query, None).await?;
+        self.decoded(query.path(), fetched.latency, Ok(fetched.body))
+    }
+
+    /// Send a `HEAD` request to the base URL so DNS, TCP and TLS are done and
+    /// the connection is pooled before the first real request
+    ///
+    /// Any response counts as success, even an error status. An `Err` only
+    /// means the connection couldn't be made, it's safe to log and carry on
+    /// since later requests will simply connect themselves.
+    pub async fn warm_up(&self) -> Result {
         self.inner
-            .get(format!("{}/api/moon/phases/{path}", self.base_url))
-            .query(query)
+            .head(&self.base_url)
+            .headers(self.headers.clone())
             .send()
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to send request: {e}"))?
+            .context("failed to warm up connection")?;
+        Ok(())
+    }
+
+    /// Fetch the body at `path`, `headers` replace client-wide headers of the
+    /// same name. The caller reports the request's success to the metrics
+    /// with [`Client::decoded`].
+    async fn get(
+        &self,
+        base_url: &str,
+        path: &str,
+        query: &impl Serialize,
+        headers: Option<&reqwest::header::HeaderMap>,
+    ) -> Result<Fetched> {
+        let mut request = self
+            .inner
+            .get(format!("{}{path}", base_url.trim_end_matches('/')))
+            .headers(self.headers.clone())
+            .query(query);
+        if let Some(headers) = headers {
+            request = request.headers(headers.clone());
+        }
+        let request = request.build().context("Failed to build request")?;
+        let cache_key = conditional_cache_key(request.url(), headers);
+        let Some(in_flight) = &self.in_flight else {
+            return self.execute(path, &cache_key, request).await;
+        };
+        let mut key = request.url().to_string();
+        if let Some(headers) = headers {
+            key.push_str(&format!(" {headers:?}"));
+        }
+        let cell = in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut latency = None;
+        let mut own_error = None;
+        let result = cell
+            .get_or_init(|| async {
+                match self.execute(path, &cache_key, request).await {
+                    Ok(fetched) => {
+                        latency = fetched.latency;
+                        Ok((fetched.body, fetched.from_cache))
+                    }
+                    Err(e) => {
+                        let message = format!("{e:#}");
+                        own_error = Some(e);
+                        Err(message)
+                    }
+                }
+            })
+            .await
+            .clone();
+        let mut in_flight = in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| std::sync::Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+        if let Some(e) = own_error {
+            return Err(e);
+        }
+        let (body, from_cache) = result.map_err(anyhow::Error::msg)?;
+        Ok(Fetched {
+            body,
+            from_cache,
+            latency,
+        })
+    }
+
+    /// Send `request`, revalidating it against the conditional cache entry
+    /// at `cache_key` when that cache is enabled
+    async fn execute(
+        &self,
+        path: &str,
+        cache_key: &str,
+        mut request: reqwest::Request,
+    ) -> Result<Fetched> {
+        use reqwest::{header, StatusCode};
+        let cached = self
+            .validators
+            .as_ref()
+            .and_then(|validators| validators.lock().unwrap().get(cache_key));
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request
+                    .headers_mut()
+                    .insert(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request
+                    .headers_mut()
+                    .insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+        for interceptor in &self.request_interceptors {
+            interceptor(&request);
+        }
+        self.record(|metrics| metrics.on_request(path));
+        let started = std::time::Instant::now();
+        let response = self
+            .inner
+            .execute(request)
+            .await
+            .inspect_err(|_| self.record(|metrics| metrics.on_error(path, RequestErrorKind::Send)))
+            .context("Failed to send request")?;
+        for interceptor in &self.response_interceptors {
+            interceptor(&response);
+        }
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(Fetched {
+                    body: cached.body,
+                    from_cache: true,
+                    latency: Some(started.elapsed()),
+                });
+            }
+        } else if status.is_redirection() {
+            self.record(|metrics| {
+                metrics.on_error(path, RequestErrorKind::Status(status.as_u16()))
+            });
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .unwrap_or_default();
+            anyhow::bail!("redirected to {location:?} ({status}) but redirects aren't followed");
+        }
+        let response = response
             .error_for_status()
-            .map_err(|e| anyhow::anyhow!("invalid status in response: {e}"))?
-            .json()
+            .inspect_err(|_| {
+                self.record(|metrics| {
+                    metrics.on_error(path, RequestErrorKind::Status(status.as_u16()))
+                })
+            })
+            .context("invalid status in response")?;
+        let etag = response.headers().get(header::ETAG).cloned();
+        let last_modified = response.headers().get(header::LAST_MODIFIED).cloned();
+        let is_html = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.contains("html"));
+        let body = response
+            .bytes()
+            .await
+            .inspect_err(|_| self.record(|metrics| metrics.on_error(path, RequestErrorKind::Body)))
+            .context("failed to read response")?;
+        // USNO serves an HTML maintenance page with a 200 while it is down
+        if is_html || body.trim_ascii_start().starts_with(b"<") {
+            self.record(|metrics| metrics.on_error(path, RequestErrorKind::Body));
+            anyhow::bail!(
+                "non-JSON response (service unavailable?): {:?}",
+                snippet(&body)
+            );
+        }
+        let latency = started.elapsed();
+        if let Some(validators) = &self.validators {
+            if etag.is_some() || last_modified.is_some() {
+                validators.lock().unwrap().insert(
+                    cache_key.to_string(),
+                    CachedBody {
+                        etag,
+                        last_modified,
+                        body: body.clone(),
+                    },
+                );
+            }
+        }
+        Ok(Fetched {
+            body,
+            from_cache: false,
+            latency: Some(latency),
+        })
+    }
+
+    /// Lazily request `days` consecutive days starting at `start`, requests are
+    /// only issued as the stream is polled and at most a few will be in flight
+    /// at once. Items are yielded in date order.
+    pub fn one_day_stream(
+        &self,
+        start: Date,
+        days: u16,
+        coords: Coords,
+        tz: f32,
+    ) -> impl Stream<Item = Result<OneDay>> + '_ {
+        futures::stream::iter(0..days)
+            .map(move |offset| async move {
+                let date = start
+                    .checked_add(time::Duration::days(offset.into()))
+                    .ok_or_else(|| anyhow::anyhow!("date out of range: {start} + {offset} days"))?;
+                self.one_day_at(date, coords, tz).await
+            })
+            .buffered(MAX_IN_FLIGHT)
+    }
+
+    /// Request every `(date, coords)` cell in `tz`, e.g. to map sunrise times
+    /// across a region
+    ///
+    /// The API has no batch form of `oneday` so this sends one request per
+    /// cell with at most `concurrency` (at least 1) in flight. The results are
+    /// in the same order as `dates_coords` and a failed cell doesn't affect
+    /// the others.
+    pub async fn one_day_grid(
+        &self,
+        dates_coords: &[(Date, Coords)],
+        tz: f32,
+        concurrency: usize,
+    ) -> Vec<Result<OneDay>> {
+        futures::stream::iter(dates_coords)
+            .map(|(date, coords)| self.one_day_at(*date, *coords, tz))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Lazily request the phases for each year in `years`, like
+    /// [`Client::one_day_stream`] requests are only issued as the stream is
+    /// polled and at most a few will be in flight at once. Items are yielded
+    /// in year order, tagged with their year.
+    pub fn phases_years(
+        &self,
+        years: RangeInclusive<u16>,
+    ) -> impl Stream<Item = Result<(u16, MoonPhasesResponse)>> + '_ {
+        futures::stream::iter(years)
+            .map(move |year| async move {
+                let phases = self.phases(&PhaseArgs::year_checked(year)?).await?;
+                Ok((year, phases))
+            })
+            .buffered(MAX_IN_FLIGHT)
+    }
+
+    /// Resolve `place` to coordinates with `geocoder` and request that location
+    /// for `date`
+    #[cfg(feature = "geocode")]
+    pub async fn one_day_for_place(
+        &self,
+        geocoder: &impl Geocoder,
+        place: &str,
+        date: Date,
+        tz: f32,
+    ) -> Result<OneDay> {
+        let coords = geocoder
+            .geocode(place)
             .await
-            .map_err(|e| anyhow::anyhow!("failed to deserialize response: {e}"))
+            .with_context(|| format!("failed to geocode {place:?}"))?;
+        self.one_day_at(date, coords, tz).await
+    }
+
+    /// [`Client::one_day_at`] with the offset resolved from the IANA zone
+    /// `zone` (e.g. `America/Chicago`) for `date`, so DST is accounted for
+    /// year-round. Requires the `tz-iana` feature, which embeds the IANA
+    /// database.
+    #[cfg(feature = "tz-iana")]
+    pub async fn one_day_in_zone(&self, date: Date, coords: Coords, zone: &str) -> Result<OneDay> {
+        let tz = iana_offset_hours(zone, date)?;
+        self.one_day_at(date, coords, tz).await
+    }
+
+    /// The moon's phase and illuminated percentage for the current UTC date
+    ///
+    /// The phase doesn't depend on location so this asks for `0, 0` at UTC.
+    pub async fn moon_phase_today(&self) -> Result<(MoonPhase, u8)> {
+        let today = self.clock.now_utc().date();
+        let data = self
+            .one_day_at(today, Coords::new(0.0, 0.0), 0.0)
+            .await?
+            .properties
+            .data;
+        Ok((data.current_phase, data.percent_illuminated.into()))
+    }
+
+    /// The first `target` phase on or after the start of `date` (UTC) with the
+    /// nearest `neighbor` phases before and after it, e.g. the next full moon
+    /// and the new moons on either side
+    ///
+    /// A window of phases from a month before `date` is requested, since the
+    /// API only reports principal phases the intermediate ones are never
+    /// found.
+    pub async fn phase_neighbors(
+        &self,
+        date: Date,
+        target: MoonPhase,
+        neighbor: MoonPhase,
+    ) -> Result<PhaseNeighbors> {
+        let start = date - time::Duration::days(32);
+        let args = PhaseArgs::build_by_date()
+            .year(start.year() as _)
+            .month_typed(start.month())
+            .day(start.day())
+            .count(16)
+            .build()?;
+        self.phases(&args)
+            .await?
+            .neighbors(date.midnight(), &target, &neighbor)
+            .ok_or_else(|| {
+                anyhow::anyhow!("no {target} with a {neighbor} on either side found around {date}")
+            })
+    }
+
+    /// A fluent alternative to building [`OneDayArgs`] and calling
+    /// [`Client::one_day`], the builder can be awaited directly instead of
+    /// calling `send`
+    ///
+    /// ```no_run
+    /// # async fn f(client: moon_unit::Client, date: time::Date) -> anyhow::Result<()> {
+    /// let data = client
+    ///     .one_day_request()
+    ///     .date(date)
+    ///     .coords(moon_unit::Coords::new(43.9033, -91.6401))
+    ///     .tz(-5.0)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[builder(start_fn = one_day_request, finish_fn = send)]
+    // named so the builder's lifetime is ours rather than one bon generates,
+    // the `IntoFuture` impl below refers to it
+    #[allow(clippy::needless_lifetimes)]
+    pub async fn one_day_at<'a>(&'a self, date: Date, coords: Coords, tz: f32) -> Result<OneDay> {
+        let args = OneDayArgs::builder()
+            .year(date.year() as _)
+            .month(date.month().into())
+            .day(date.day())
+            .lat(coords.lat)
+            .long(coords.long)
+            .tz(tz)
+            .build()?;
+        self.one_day(&args).await
+    }
+}
+
+/// Awaiting the builder sends it, so `.send()` can be left off
+impl<'a, S: client_one_day_at_builder::IsComplete + 'a> std::future::IntoFuture
+    for ClientOneDayAtBuilder<'a, S>
+{
+    type Output = Result<OneDay>;
+    type IntoFuture = futures::future::BoxFuture<'a, Result<OneDay>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+/// Write `phases` to `path`, through a temporary file so a concurrent reader
+/// never sees a partial file
+#[cfg(feature = "disk-cache")]
+async fn write_disk_cache(path: &std::path::Path, phases: &MoonPhasesResponse) -> Result {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    tokio::fs::write(&tmp, serde_json::to_vec(phases)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+/// A month of daily events and the moon phases that fall in it
+///
+/// ```no_run
+/// # async fn f(client: moon_unit::Client) -> anyhow::Result<()> {
+/// let calendar = moon_unit::MoonCalendar::builder(&client)
+///     .year(2025)
+///     .month(time::Month::April)
+///     .coords(moon_unit::Coords::new(43.9033, -91.6401))
+///     .tz(-5.0)
+///     .fetch()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MoonCalendar {
+    pub year: i32,
+    pub month: time::Month,
+    /// One entry per day of the month, in order
+    pub days: Vec<OneDay>,
+    /// The principal phases that happen during the month, in order
+    pub phases: Vec<MoonPhaseEntry>,
+}
+
+#[bon::bon]
+impl MoonCalendar {
+    /// The daily requests share the bounded concurrency of
+    /// [`Client::one_day_stream`] and run alongside the phases request.
+    #[builder(finish_fn = fetch)]
+    pub async fn new(
+        #[builder(start_fn)] client: &Client,
+        year: i32,
+        month: time::Month,
+        coords: Coords,
+        tz: f32,
+    ) -> Result<Self> {
+        let start = Date::from_calendar_date(year, month, 1)
+            .map_err(|e| anyhow::anyhow!("invalid date: {e}"))?;
+        let days_in_month = month.length(year);
+        let phases_args = PhaseArgs::build_by_date()
+            .year(year as _)
+            .month_typed(month)
+            .day(1)
+            // enough to cover every principal phase in the longest month
+            .count(6)
+            .build()?;
+        let (days, phases) = futures::try_join!(
+            client
+                .one_day_stream(start, days_in_month.into(), coords, tz)
+                .try_collect::<Vec<_>>(),
+            client.phases(&phases_args),
+        )?;
+        let phases = phases
+            .phases
+            .into_iter()
+            .filter(|p| i32::from(p.year) == year && p.month == u8::from(month))
+            .collect();
+        Ok(Self {
+            year,
+            month,
+            days,
+            phases,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Coords {
+    pub lat: f32,
+    pub long: f32,
+}
+
+impl Coords {
+    pub fn new(lat: f32, long: f32) -> Self {
+        Self { lat, long }
+    }
+
+    /// Pull the coordinates out of a link copied from a map site
+    ///
+    /// Recognizes a `lat,long` in a `q`, `query`, `ll` or `center` query
+    /// parameter (`https://maps.google.com/?q=43.9033,-91.6401`), a path
+    /// segment like `@43.9033,-91.6401,15z`, OpenStreetMap's `mlat`/`mlon`
+    /// parameters and its `#map=zoom/lat/long` fragment. Fails if none of
+    /// these are present or the coordinates are out of range.
+    pub fn from_map_url(url: &str) -> Result<Coords> {
+        let parsed =
+            reqwest::Url::parse(url).with_context(|| format!("invalid map URL: {url:?}"))?;
+        let pair = |value: &str, separator| {
+            let mut parts = value.splitn(3, separator);
+            let lat = parts.next()?.trim().parse().ok()?;
+            let long = parts.next()?.trim().parse().ok()?;
+            Some(Coords::new(lat, long))
+        };
+        let param = |name: &str| {
+            parsed
+                .query_pairs()
+                .find_map(|(key, value)| (key == name).then(|| value.parse::<f32>().ok())?)
+        };
+        let coords = parsed
+            .query_pairs()
+            .filter(|(key, _)| matches!(&**key, "q" | "query" | "ll" | "center"))
+            .find_map(|(_, value)| pair(&value, ','))
+            .or_else(|| {
+                parsed
+                    .path()
+                    .split('/')
+                    .find_map(|segment| pair(segment.strip_prefix('@')?, ','))
+            })
+            .or_else(|| Some(Coords::new(param("mlat")?, param("mlon")?)))
+            .or_else(|| {
+                let (_, position) = parsed.fragment()?.strip_prefix("map=")?.split_once('/')?;
+                pair(position, '/')
+            })
+            .with_context(|| format!("no coordinates found in map URL: {url}"))?;
+        if !(-90.0..=90.0).contains(&coords.lat) || !(-180.0..=180.0).contains(&coords.long) {
+            anyhow::bail!("coordinates out of range in map URL: {url}");
+        }
+        Ok(coords)
+    }
+
+    /// Great-circle distance to `other` in kilometers
+    pub fn distance_to(&self, other: &Coords) -> f32 {
+        const EARTH_RADIUS_KM: f32 = 6371.0;
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_long = (other.long - self.long).to_radians();
+        let a =
+            (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_long / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// A rough timezone for this location, `long / 15` rounded to the nearest
+    /// hour.
+    ///
+    /// This is only an approximation based on the solar meridian, it isn't
+    /// aware of political timezone boundaries or daylight saving time.
+    pub fn approx_tz(&self) -> f32 {
+        (self.long / 15.0).round()
+    }
+
+    /// Format as degrees, minutes and seconds with hemisphere suffixes, e.g.
+    /// `43°54'12"N 91°38'24"W`. Seconds are rounded to the nearest whole
+    /// second, the equator and prime meridian are `N` and `E`.
+    pub fn to_dms(&self) -> String {
+        format!("{} {}", dms(self.lat, 'N', 'S'), dms(self.long, 'E', 'W'))
     }
 }
 
+/// Format one coordinate as `D°M'S"` followed by `positive` or `negative`
+fn dms(value: f32, positive: char, negative: char) -> String {
+    let hemisphere = if value < 0.0 { negative } else { positive };
+    let seconds = (f64::from(value).abs() * 3600.0).round() as u32;
+    format!(
+        "{}°{}'{}\"{hemisphere}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Resolves a place name into [`Coords`], no implementation is provided by
+/// this crate.
+#[cfg(feature = "geocode")]
+pub trait Geocoder {
+    fn geocode(&self, place: &str) -> impl std::future::Future<Output = Result<Coords>> + Send;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct OneDayArgs {
     date: String,
     coords: String,
     tz: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
 }
 
 #[bon::bon]
 impl OneDayArgs {
+    /// `id` is an optional label the server echoes back as
+    /// [`OneDayData::label`], useful for matching responses to requests
+    ///
+    /// Fails if `year` is outside `supported_years`, which defaults to
+    /// [`SUPPORTED_YEARS`].
     #[builder]
-    pub fn new(year: u16, month: u8, day: u8, lat: f32, long: f32, tz: f32) -> Self {
-        Self {
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        lat: f32,
+        long: f32,
+        tz: f32,
+        #[builder(into)] id: Option<String>,
+        #[builder(default = SUPPORTED_YEARS)] supported_years: RangeInclusive<u16>,
+    ) -> Result<Self> {
+        check_year(year, &supported_years)?;
+        Ok(Self {
             date: format!("{year:04}-{month:02}-{day:02}"),
             coords: format!("{lat:.04},{long:.04}"),
             tz,
-        }
+            id,
+        })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum PhaseArgs {
-    Year { year: u16 },
-    ByDate { date: String, nump: u16 },
+impl OneDayArgs {
+    /// Arguments for today at `coords` in the system's current UTC offset
+    ///
+    /// Fails if the local offset can't be determined, on some Unix platforms
+    /// `time` refuses to read it while other threads may be running.
+    #[cfg(feature = "localtz")]
+    pub fn for_local_date(coords: Coords) -> Result<Self> {
+        let offset = time::UtcOffset::current_local_offset()
+            .context("failed to determine the local UTC offset")?;
+        let today = OffsetDateTime::now_utc().to_offset(offset).date();
+        Self::builder()
+            .year(today.year() as _)
+            .month(today.month().into())
+            .day(today.day())
+            .lat(coords.lat)
+            .long(coords.long)
+            .utc_offset(offset)?
+            .build()
+    }
 }
 
-#[bon::bon]
-impl PhaseArgs {
-    pub fn year(year: u16) -> Self {
-        Self::Year { year: year }
-    }
+/// Parse the compact form `DATE@LAT,LONG [tz=HOURS] [id=LABEL]`, e.g.
+/// `2025-04-25@43.9,-91.6 tz=-5`
+///
+/// `DATE` is `YYYY-MM-DD`, `tz` defaults to `0` and the optional settings are
+/// separated by whitespace and may come in any order. The year must be in
+/// [`SUPPORTED_YEARS`].
+impl std::str::FromStr for OneDayArgs {
+    type Err = anyhow::Error;
 
-    #[builder(
-        start_fn = build_by_date,
-        finish_fn = build,
-    )]
-    pub fn by_date(year: u16, month: u8, day: u8, count: u16) -> Result<Self> {
-        if count < 1 || count > 99 {
-            anyhow::bail!("Invalid count, must be between 1 and 99 inclusive found: {count}")
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split_whitespace();
+        let head = parts.next().context("empty one day args")?;
+        let (date, coords) = head
+            .split_once('@')
+            .with_context(|| format!("expected DATE@LAT,LONG found: {head:?}"))?;
+        let mut date_parts = date.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) =
+            (date_parts.next(), date_parts.next(), date_parts.next())
+        else {
+            anyhow::bail!("expected a YYYY-MM-DD date found: {date:?}");
+        };
+        let (lat, long) = coords
+            .split_once(',')
+            .with_context(|| format!("expected LAT,LONG found: {coords:?}"))?;
+        let mut tz = 0.0;
+        let mut id = None;
+        for part in parts {
+            match part.split_once('=') {
+                Some(("tz", value)) => {
+                    tz = value
+                        .parse()
+                        .with_context(|| format!("invalid tz: {value:?}"))?
+                }
+                Some(("id", value)) => id = Some(value.to_string()),
+                _ => anyhow::bail!("unknown setting {part:?}, expected tz=HOURS or id=LABEL"),
+            }
         }
-        Ok(Self::ByDate {
-            date: format!("{year:04}-{month:02}-{day:02}"),
-            nump: count,
-        })
+        Self::builder()
+            .year(
+                year.parse()
+                    .with_context(|| format!("invalid year: {year:?}"))?,
+            )
+            .month(
+                month
+                    .parse()
+                    .with_context(|| format!("invalid month: {month:?}"))?,
+            )
+            .day(
+                day.parse()
+                    .with_context(|| format!("invalid day: {day:?}"))?,
+            )
+            .lat(
+                lat.parse()
+                    .with_context(|| format!("invalid latitude: {lat:?}"))?,
+            )
+            .long(
+                long.parse()
+                    .with_context(|| format!("invalid longitude: {long:?}"))?,
+            )
+            .tz(tz)
+            .maybe_id(id)
+            .build()
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OneDay {
-    pub properties: OneDayProps,
+impl TryFrom<&str> for OneDayArgs {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OneDayProps {
-    pub data: OneDayData,
+/// The years the API can compute data for, the builders reject years outside
+/// this range unless they are given their own
+pub const SUPPORTED_YEARS: RangeInclusive<u16> = 1700..=2100;
+
+fn check_year(year: u16, supported: &RangeInclusive<u16>) -> Result {
+    if !supported.contains(&year) {
+        anyhow::bail!(
+            "Invalid year, must be between {} and {} inclusive found: {year}",
+            supported.start(),
+            supported.end()
+        )
+    }
+    Ok(())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OneDayData {
-    #[serde(alias = "closestphase")]
-    pub closest_phase: ClosestPhase,
-    #[serde(alias = "curphase")]
-    pub current_phase: MoonPhase,
-    pub day_of_week: String,
-    #[serde(alias = "fracillum")]
-    #[serde(deserialize_with = "deser_fracillum")]
-    pub percent_illuminated: u8,
-    #[serde(alias = "moondata")]
-    pub moon_data: Vec<CelestialEvent>,
-    #[serde(alias = "sundata")]
-    pub sun_data: Vec<CelestialEvent>,
-    month: u8,
-    day: u8,
-    year: u16,
-    tz: f32,
-}
+impl<S: one_day_args_builder::State> OneDayArgsBuilder<S> {
+    /// Set the month from a [`time::Month`] rather than its number
+    pub fn month_typed(
+        self,
+        month: time::Month,
+    ) -> OneDayArgsBuilder<one_day_args_builder::SetMonth<S>>
+    where
+        S::Month: one_day_args_builder::IsUnset,
+    {
+        self.month(month.into())
+    }
 
-impl OneDayData {
-    pub fn when(&self) -> Result<OffsetDateTime> {
-        let month = time::Month::try_from(self.month).map_err(|e| {
-            anyhow::anyhow!("Invalid month in date: {e}")
-        })?;
-        let dt = Date::from_calendar_date(self.year as _, month, self.day).map_err(|e| {
-            anyhow::anyhow!("invalid date: {e}")
-        })?;
-        let time = time::Time::MIDNIGHT;
-        let tz_hour = self.tz.floor() as i8;
-        let tz_minute = (self.tz.rem(1.0) * 60.0) as i8;
-        let tz = time::UtcOffset::from_hms(tz_hour, tz_minute, 0).unwrap_or(time::UtcOffset::UTC);
-        Ok(OffsetDateTime::new_in_offset(dt, time, tz))
+    /// Set `tz` from a [`time::UtcOffset`]
+    ///
+    /// The API expects the offset in fractional hours, this is computed as
+    /// `hours + minutes / 60` with both components carrying the offset's sign,
+    /// so `+05:30` becomes `5.5`, `+05:45` becomes `5.75` and `-03:30` becomes
+    /// `-3.5`. Offsets with a non-zero seconds component can't be represented
+    /// and are rejected.
+    pub fn utc_offset(
+        self,
+        offset: time::UtcOffset,
+    ) -> Result<OneDayArgsBuilder<one_day_args_builder::SetTz<S>>>
+    where
+        S::Tz: one_day_args_builder::IsUnset,
+    {
+        Ok(self.tz(hours_from_offset(offset)?))
     }
 
+    /// Set `tz` from hours written in `convention`, converting to the west
+    /// negative offsets the API expects. `.tz(-5.0)` is the same as
+    /// `.tz_in(5.0, TzConvention::WestPositive)`.
+    pub fn tz_in(
+        self,
+        tz: f32,
+        convention: TzConvention,
+    ) -> OneDayArgsBuilder<one_day_args_builder::SetTz<S>>
+    where
+        S::Tz: one_day_args_builder::IsUnset,
+    {
+        self.tz(convention.to_iso(tz))
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClosestPhase {
-    day: u8,
-    month: u8,
-    year: u16,
-    #[serde(deserialize_with = "deser_time")]
-    time: Time,
-    pub phase: MoonPhase,
+/// Which sign a UTC offset in hours has for zones west of Greenwich
+///
+/// USNO follows ISO 8601 where the Americas are negative (US Central
+/// Daylight Time is `-5`), this is what `tz` means everywhere in this crate.
+/// Some tools count the other way, POSIX `TZ` strings like `CST6CDT` and
+/// JavaScript's `Date.getTimezoneOffset` are positive to the west, offsets
+/// copied from those can be passed with [`OneDayArgsBuilder::tz_in`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TzConvention {
+    /// West is negative, e.g. `-5` for UTC-05:00
+    #[default]
+    Iso,
+    /// West is positive, e.g. `5` for UTC-05:00
+    WestPositive,
 }
 
-impl ClosestPhase {
-    pub fn when(&self) -> Result<PrimitiveDateTime> {
-        let month = time::Month::try_from(self.month).map_err(|e| {
-            anyhow::anyhow!("Invalid month in date: {e}")
-        })?;
-        let dt = Date::from_calendar_date(self.year as _, month, self.day).map_err(|e| {
-            anyhow::anyhow!("invalid date: {e}")
-        })?;
-        let t = time::Time::from_hms(self.time.hour, self.time.minute, 0).map_err(|e| {
-            anyhow::anyhow!("invalid time: {e}")
-        })?;
-        Ok(PrimitiveDateTime::new(dt, t))
+impl TzConvention {
+    /// `tz` written in this convention as an ISO (west negative) offset
+    pub fn to_iso(self, tz: f32) -> f32 {
+        match self {
+            Self::Iso => tz,
+            // rather than `-tz` so `0` doesn't become `-0`
+            Self::WestPositive => 0.0 - tz,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum MoonPhase {
-    #[serde(alias = "New Moon")]
-    New,
-    #[serde(alias = "Waxing Crescent")]
-    WaxingCrescent,
-    #[serde(alias = "First Quarter")]
-    FirstQuarter,
-    #[serde(alias = "Waxing Gibbous")]
-    WaxingGibbous,
-    #[serde(alias = "Full Moon")]
-    Full,
-    #[serde(alias = "Waning Gibbous")]
-    WaningGibbous,
-    #[serde(alias = "Last Quarter")]
-    LastQuarter,
-    #[serde(alias = "Waning Crescent")]
-    WaningCrescent,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum PhaseArgs {
+    Year { year: u16 },
+    ByDate {
+        date: String,
+        nump: u16,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tz: Option<f32>,
+    },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum Phenomenon {
-    Rise,
-    #[serde(alias = "Upper Transit")]
-    Apex,
-    #[serde(alias = "Begin Civil Twilight")]
-    TwilightBegins,
-    Set,
-    #[serde(alias = "End Civil Twilight")]
-    TwilightEnds,
-}
+#[bon::bon]
+impl PhaseArgs {
+    fn path(&self) -> &'static str {
+        match self {
+            Self::Year { .. } => "/api/moon/phases/year",
+            Self::ByDate { .. } => "/api/moon/phases/date",
+        }
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CelestialEvent {
-    #[serde(alias = "phen")]
-    pub phenomenon: Phenomenon,
-    #[serde(deserialize_with = "deser_time")]
-    time: Time,
-}
+    /// A file name safe key unique to these arguments
+    #[cfg(feature = "disk-cache")]
+    fn cache_key(&self) -> String {
+        match self {
+            Self::Year { year } => format!("year-{year}"),
+            Self::ByDate {
+                date,
+                nump,
+                tz: None,
+            } => format!("date-{date}-{nump}"),
+            Self::ByDate {
+                date,
+                nump,
+                tz: Some(tz),
+            } => format!("date-{date}-{nump}-tz{tz}"),
+        }
+    }
 
-impl CelestialEvent {
-    pub fn when(&self) -> Result<time::Time> {
-        time::Time::from_hms(self.time.hour, self.time.minute, 0).map_err(|e| {
-            anyhow::anyhow!("invalid time: {e}")
+    /// Every phase in `year`, this isn't checked against
+    /// [`SUPPORTED_YEARS`] so an unsupported year is only rejected by the
+    /// server, see [`PhaseArgs::year_checked`]
+    pub fn year(year: u16) -> Self {
+        Self::Year { year }
+    }
+
+    /// [`PhaseArgs::year`] failing if `year` is outside [`SUPPORTED_YEARS`]
+    pub fn year_checked(year: u16) -> Result<Self> {
+        check_year(year, &SUPPORTED_YEARS)?;
+        Ok(Self::Year { year })
+    }
+
+    /// Phases starting at a date, `tz` has the phase times reported in that
+    /// offset (in hours, ISO convention) instead of UTC
+    ///
+    /// A phase happens at the same instant everywhere so only the clock time
+    /// (and possibly the date) reported depends on `tz`, not the location.
+    #[builder(
+        start_fn = build_by_date,
+        finish_fn = build,
+    )]
+    pub fn by_date(
+        #[builder(getter)] year: u16,
+        #[builder(getter)] month: u8,
+        #[builder(getter)] day: u8,
+        count: u16,
+        tz: Option<f32>,
+        #[builder(default = SUPPORTED_YEARS)] supported_years: RangeInclusive<u16>,
+    ) -> Result<Self> {
+        check_year(year, &supported_years)?;
+        if !(1..=99).contains(&count) {
+            anyhow::bail!("Invalid count, must be between 1 and 99 inclusive found: {count}")
+        }
+        Ok(Self::ByDate {
+            date: format!("{year:04}-{month:02}-{day:02}"),
+            nump: count,
+            tz,
         })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Time {
-    hour: u8,
-    minute: u8,
-}
+impl<S: phase_args_by_date_builder::State> PhaseArgsByDateBuilder<S> {
+    /// Set the month from a [`time::Month`] rather than its number
+    pub fn month_typed(
+        self,
+        month: time::Month,
+    ) -> PhaseArgsByDateBuilder<phase_args_by_date_builder::SetMonth<S>>
+    where
+        S::Month: phase_args_by_date_builder::IsUnset,
+    {
+        self.month(month.into())
+    }
 
-fn deser_fracillum<'de, D>(d: D) -> Result<u8, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct FracillumVisitor;
-    impl<'de> serde::de::Visitor<'de> for FracillumVisitor {
-        type Value = u8;
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str(r"time with the format \d{2}:\d{2}")
+    /// Set the count to enough phases to reach `end` from the start date
+    /// instead of calling `count`, the last phase may fall a few days past
+    /// `end`
+    ///
+    /// Principal phases are about 7.4 days apart so a request covers a
+    /// little under two years, fails if `end` is further out than that (use
+    /// [`Client::phases_years`] or several requests instead), before the start
+    /// date or if the start date is invalid.
+    pub fn through(
+        self,
+        end: Date,
+    ) -> Result<PhaseArgsByDateBuilder<phase_args_by_date_builder::SetCount<S>>>
+    where
+        S::Year: phase_args_by_date_builder::IsSet,
+        S::Month: phase_args_by_date_builder::IsSet,
+        S::Day: phase_args_by_date_builder::IsSet,
+        S::Count: phase_args_by_date_builder::IsUnset,
+    {
+        let month = time::Month::try_from(*self.get_month())
+            .map_err(|e| anyhow::anyhow!("Invalid month in date: {e}"))?;
+        let start = Date::from_calendar_date(i32::from(*self.get_year()), month, *self.get_day())
+            .map_err(|e| anyhow::anyhow!("invalid date: {e}"))?;
+        if end < start {
+            anyhow::bail!("end date {end} is before the start date {start}");
         }
-        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            v.trim_end_matches('%').parse().map_err(|e| {
-                serde::de::Error::custom(format!("Failed ot parse precent: {e}\n\t{v:?}"))
-            })
+        let days = (end - start).whole_days() as f32;
+        let count = (days / (SYNODIC_MONTH_DAYS / 4.0)).ceil() as u32 + 1;
+        if count > 99 {
+            anyhow::bail!(
+                "{start} through {end} needs {count} phases but a request allows at most 99, \
+                 use Client::phases_years or split the range"
+            );
+        }
+        Ok(self.count(count as u16))
+    }
+}
+
+/// Response from the `oneday` endpoint.
+///
+/// The response models only rely on `serde`'s derives, the JSON backend is
+/// left to the caller so they can be deserialized with `serde_json`,
+/// `simd-json` or any other self describing `serde` format.
+///
+/// Besides the usual `{ "properties": { "data": ... } }` envelope a bare array
+/// of day data, as some USNO revisions send, is accepted. Only the first day
+/// of the array is kept.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OneDay {
+    pub properties: OneDayProps,
+    /// The location the server computed the day for, not every response
+    /// includes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Geometry>,
+    #[serde(skip)]
+    from_cache: bool,
+    #[serde(skip)]
+    fetched_at: Option<OffsetDateTime>,
+    #[serde(skip)]
+    args: Option<OneDayArgs>,
+}
+
+impl<'de> Deserialize<'de> for OneDay {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Envelope {
+            properties: OneDayProps,
+            #[serde(default)]
+            geometry: Option<Geometry>,
         }
 
-        fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            Ok(v)
+        struct OneDayVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for OneDayVisitor {
+            type Value = OneDay;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a oneday response or an array of day data")
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let envelope =
+                    Envelope::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+                Ok(OneDay::new(envelope.properties.data, envelope.geometry))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let data = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok(OneDay::new(data, None))
+            }
         }
+
+        d.deserialize_any(OneDayVisitor)
     }
-    d.deserialize_any(FracillumVisitor)
 }
 
-fn deser_time<'de, D>(d: D) -> Result<Time, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct TimeVisitor;
-    impl<'de> serde::de::Visitor<'de> for TimeVisitor {
-        type Value = Time;
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("number and percent")
+impl OneDay {
+    fn new(data: OneDayData, geometry: Option<Geometry>) -> Self {
+        Self {
+            properties: OneDayProps { data },
+            geometry,
+            from_cache: false,
+            fetched_at: None,
+            args: None,
         }
-        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            let (hours, minutes) = v
-                .split_once(":")
-                .ok_or_else(|| serde::de::Error::custom(format!("time missing colon: {v:?}")))?;
-            Ok(Time {
-                hour: hours
-                    .parse()
-                    .map_err(|e| serde::de::Error::custom(format!("invalid hour-{e}: {v:?}")))?,
-                minute: minutes
-                    .parse()
-                    .map_err(|e| serde::de::Error::custom(format!("invalid minute-{e}: {v:?}")))?,
-            })
+    }
+
+    /// `true` when this response was revalidated with the server and served
+    /// from the client's conditional cache
+    pub fn from_cache(&self) -> bool {
+        self.from_cache
+    }
+
+    /// When the [`Client`] received this response, `None` if it was
+    /// deserialized some other way
+    pub fn fetched_at(&self) -> Option<OffsetDateTime> {
+        self.fetched_at
+    }
+
+    /// The arguments the [`Client`] requested this response with, `None` if
+    /// it was deserialized some other way. Like `fetched_at` this isn't part
+    /// of the serialized form.
+    pub fn args(&self) -> Option<&OneDayArgs> {
+        self.args.as_ref()
+    }
+
+    /// If `other` reports the same astronomical data as this day, for
+    /// deciding whether upstream data actually changed
+    ///
+    /// Metadata is ignored: `fetched_at`, `from_cache`, `args`, the echoed
+    /// `geometry` and the `label` from the request.
+    pub fn same_data(&self, other: &OneDay) -> bool {
+        let content = |day: &OneDay| {
+            let mut value = serde_json::to_value(&day.properties.data).ok()?;
+            value.as_object_mut()?.remove("label");
+            Some(value)
+        };
+        match (content(self), content(other)) {
+            (Some(this), Some(other)) => this == other,
+            _ => false,
         }
+    }
 
-        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-        where
-            A: serde::de::MapAccess<'de>,
-        {
-            let mut hour = None;
-            let mut minute = None;
-            while let Some(key) = map.next_key::<&str>()? {
-                match key {
-                    "hour" => {
-                        hour = Some(map.next_value::<u8>()?);
-                    }
-                    "minute" => {
-                        minute = Some(map.next_value::<u8>()?);
-                    }
-                    _ => {}
+    /// The sun and moon events of every day in `days` as one timeline, sorted
+    /// by instant then body and phenomenon. The same event reported by two
+    /// days, e.g. around midnight or because a day was fetched twice, is only
+    /// included once. Fails if a day's date, `tz` or an event time is invalid.
+    pub fn merge_days(days: &[OneDay]) -> Result<Vec<TimelineEvent>> {
+        let mut timeline = Vec::new();
+        for day in days {
+            let data = &day.properties.data;
+            let start = data.when()?;
+            let bodies = [(Body::Sun, &data.sun_data), (Body::Moon, &data.moon_data)];
+            for (body, events) in bodies {
+                for event in events {
+                    timeline.push(TimelineEvent {
+                        body,
+                        phenomenon: event.phenomenon,
+                        when: start.replace_time(event.when()?),
+                    });
                 }
             }
-            let hour = hour.ok_or_else(|| serde::de::Error::custom("hour missing from map"))?;
-            let minute =
-                minute.ok_or_else(|| serde::de::Error::custom("minute missing from map"))?;
-            Ok(Time { hour, minute })
         }
+        timeline.sort_by_key(|event| (event.when, event.body, event.phenomenon));
+        timeline.dedup();
+        Ok(timeline)
     }
-    d.deserialize_any(TimeVisitor)
+
+    /// Like [`merge_days`](Self::merge_days) with each moon event paired with
+    /// the moon's estimated illumination at that instant (see
+    /// [`OneDayData::illumination_at`]), sun events are paired with `None`.
+    pub fn merge_days_illuminated(days: &[OneDay]) -> Result<Vec<(TimelineEvent, Option<f32>)>> {
+        let mut timeline = Vec::new();
+        for day in days {
+            let data = &day.properties.data;
+            for event in Self::merge_days(std::slice::from_ref(day))? {
+                let illumination = match event.body {
+                    Body::Moon => data.illumination_at(event.when),
+                    Body::Sun => None,
+                };
+                timeline.push((event, illumination));
+            }
+        }
+        timeline.sort_by_key(|(event, _)| (event.when, event.body, event.phenomenon));
+        timeline.dedup_by(|a, b| a.0 == b.0);
+        Ok(timeline)
+    }
+}
+
+/// A celestial body the API reports events for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Body {
+    Sun,
+    Moon,
+}
+
+/// One event on a multi-day timeline, see [`OneDay::merge_days`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    pub body: Body,
+    pub phenomenon: Phenomenon,
+    pub when: OffsetDateTime,
 }
 
+/// The GeoJSON geometry the server echoes back, for a `oneday` response this
+/// is a `Point` at the requested coordinates
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MoonPhasesResponse {
-    #[serde(alias = "numphases")]
-    pub count: u16,
-    #[serde(alias = "phasedata")]
-    pub phases: Vec<MoonPhaseEntry>,
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Geometry {
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+    /// `[longitude, latitude]`, in GeoJSON order. Empty for other geometries
+    /// whose coordinates are nested arrays.
+    #[serde(default, deserialize_with = "lenient_coordinates")]
+    pub coordinates: Vec<f32>,
+}
+
+impl Geometry {
+    /// The echoed location, `None` unless this is a point with both
+    /// coordinates. GeoJSON puts longitude first, comparing this to the
+    /// requested [`Coords`] catches a swapped latitude and longitude.
+    pub fn coords(&self) -> Option<Coords> {
+        if self.kind.as_deref().is_some_and(|kind| kind != "Point") {
+            return None;
+        }
+        match self.coordinates.as_slice() {
+            [long, lat, ..] => Some(Coords::new(*lat, *long)),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MoonPhaseEntry {
-    pub phase: MoonPhase,
-    day: u8,
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OneDayProps {
+    pub data: OneDayData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OneDayData {
+    #[serde(alias = "closestphase")]
+    pub closest_phase: ClosestPhase,
+    #[serde(alias = "curphase")]
+    pub current_phase: MoonPhase,
+    pub day_of_week: String,
+    #[serde(alias = "fracillum")]
+    pub percent_illuminated: Illumination,
+    #[serde(alias = "moondata")]
+    pub moon_data: Vec<CelestialEvent>,
+    #[serde(alias = "sundata")]
+    pub sun_data: Vec<CelestialEvent>,
+    /// The distance from the observer to the moon in kilometers, not every
+    /// response includes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moon_distance_km: Option<f32>,
+    /// The apparent diameter of the moon's disk in arcminutes, not every
+    /// response includes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moon_angular_diameter: Option<f32>,
+    /// The `id` from the request, if one was provided
+    #[serde(default)]
+    pub label: Option<String>,
+    /// If the server considered daylight saving time to be in effect for
+    /// `tz`, `None` when it's absent or not a recognizable boolean
+    #[serde(
+        default,
+        deserialize_with = "lenient_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub isdst: Option<bool>,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
     month: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    day: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
     year: u16,
-    #[serde(alias = "deser_time")]
-    time: Time,
+    #[serde(deserialize_with = "lenient_f32")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_number")
+    )]
+    tz: f32,
 }
 
-impl MoonPhaseEntry {
-    pub fn when(&self) -> Result<PrimitiveDateTime> {
-        let month = time::Month::try_from(self.month).map_err(|e| {
-            anyhow::anyhow!("Invalid month in date: {e}")
-        })?;
-        let dt = Date::from_calendar_date(self.year as _, month, self.day).map_err(|e| {
-            anyhow::anyhow!("invalid date: {e}")
-        })?;
-        let t = time::Time::from_hms(self.time.hour, self.time.minute, 0).map_err(|e| {
-            anyhow::anyhow!("invalid time: {e}")
-        })?;
-        Ok(PrimitiveDateTime::new(dt, t))
+impl OneDayData {
+    pub fn when(&self) -> Result<OffsetDateTime> {
+        start_of_day(self.year, self.month, self.day, self.tz)
     }
-}
 
+    /// Drop the moon fields, keeping the sun events (including twilight) and
+    /// the date. The API has no sun-only query so this doesn't make the
+    /// request any lighter, it reduces what large batches keep in memory.
+    pub fn into_sun_only(self) -> SunOnlyDay {
+        SunOnlyDay {
+            day_of_week: self.day_of_week,
+            sun_data: self.sun_data,
+            label: self.label,
+            month: self.month,
+            day: self.day,
+            year: self.year,
+            tz: self.tz,
+        }
+    }
 
+    /// `true` if the date in the response is `requested`, the API can shift
+    /// the date when `tz` pushes the day across midnight so batch code can
+    /// use this to flag those days
+    pub fn requested_date_matches(&self, requested: Date) -> bool {
+        i32::from(self.year) == requested.year()
+            && self.month == u8::from(requested.month())
+            && self.day == requested.day()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn one_day_args() {
-        insta::assert_json_snapshot!(OneDayArgs::builder()
-            .year(2025)
-            .month(4)
-            .day(25)
-            .tz(0.0)
-            .lat(0.0)
+    /// Correct the event times for a server that computed them in whole hours
+    /// when `intended` has a fraction (e.g. `5.5` or `-3.5`)
+    ///
+    /// The times are assumed to be in the whole hour part of the `tz` the
+    /// server echoed, which covers a server that truncates the fraction and
+    /// echoes the requested `tz` or one that echoes the whole hours it used.
+    /// Each event and the `closest_phase` are shifted by the difference and
+    /// `tz` is set to `intended`, an event shifted past midnight wraps around
+    /// but stays in this day's list while the closest phase's date moves with
+    /// it. Nothing changes when the difference is under a minute. Apply it
+    /// only once, a corrected day looks like one that still needs correcting.
+    ///
+    /// Fails, leaving the day unchanged, if `intended` or `tz` isn't a valid
+    /// UTC offset or the closest phase's date is invalid.
+    pub fn apply_fractional_tz(&mut self, intended: f32) -> Result {
+        offset_from_hours(intended)?;
+        self.utc_offset()?;
+        // both are under 26 hours so this can't overflow
+        let minutes = ((intended - self.tz.trunc()) * 60.0).round() as i32;
+        if minutes == 0 {
+            return Ok(());
+        }
+        let closest = self.closest_phase.when()? + time::Duration::minutes(minutes.into());
+        self.closest_phase.year = closest.year() as u16;
+        self.closest_phase.month = closest.month().into();
+        self.closest_phase.day = closest.day();
+        self.closest_phase.time = Time {
+            hour: closest.hour(),
+            minute: closest.minute(),
+            second: closest.second(),
+        };
+        for event in self.sun_data.iter_mut().chain(&mut self.moon_data) {
+            let total = (i32::from(event.time.hour) * 60 + i32::from(event.time.minute) + minutes)
+                .rem_euclid(24 * 60);
+            event.time = Time {
+                hour: (total / 60) as u8,
+                minute: (total % 60) as u8,
+                second: event.time.second,
+            };
+        }
+        self.tz = intended;
+        Ok(())
+    }
+
+    /// `tz` as a [`time::UtcOffset`], fails if it is out of range
+    pub fn utc_offset(&self) -> Result<time::UtcOffset> {
+        offset_from_hours(self.tz)
+    }
+
+    /// The age of the moon, in days since the last new moon, at the start of
+    /// this day.
+    ///
+    /// This is derived from the `closest_phase`, the nominal age of that phase
+    /// is offset by the time between it and the start of the day and wrapped
+    /// into a mean synodic month. Since the true lunation length varies by
+    /// several hours from the mean, this is accurate to within about a day.
+    pub fn moon_age(&self) -> Result<f32> {
+        let start = PrimitiveDateTime::new(self.when()?.date(), time::Time::MIDNIGHT);
+        let closest = self.closest_phase.when()?;
+        let offset = (start - closest).as_seconds_f32() / 86_400.0;
+        let nominal = self.closest_phase.phase.typical_age_days().ok_or_else(|| {
+            anyhow::anyhow!("unknown closest phase: {}", self.closest_phase.phase)
+        })?;
+        Ok((nominal + offset).rem_euclid(SYNODIC_MONTH_DAYS))
+    }
+
+    /// Check some basic invariants to weed out malformed responses, nothing
+    /// calls this automatically
+    ///
+    /// Fails if the illumination is over 100%, the `closest_phase` is more
+    /// than 5 days from the requested date (principal phases are ~7.4 days
+    /// apart) or the illumination is implausible for `current_phase`.
+    pub fn self_check(&self) -> Result {
+        let illumination = self.percent_illuminated.0;
+        if illumination > 100 {
+            anyhow::bail!("illumination out of range: {illumination}%");
+        }
+        let start = PrimitiveDateTime::new(self.when()?.date(), time::Time::MIDNIGHT);
+        let closest = self.closest_phase.when()?;
+        if (closest - start).abs() > time::Duration::days(5) {
+            anyhow::bail!(
+                "closest phase {} on {} is too far from {}",
+                self.closest_phase.phase,
+                closest.date(),
+                start.date()
+            );
+        }
+        if let Some(range) = self.current_phase.plausible_illumination() {
+            if !range.contains(&illumination) {
+                anyhow::bail!(
+                    "illumination {illumination}% is inconsistent with {}",
+                    self.current_phase
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// If the `closest_phase` happens after the start of this day, `false`
+    /// means the closest principal phase has already passed.
+    pub fn closest_phase_is_future(&self) -> Result<bool> {
+        let start = PrimitiveDateTime::new(self.when()?.date(), time::Time::MIDNIGHT);
+        Ok(self.closest_phase.when()? >= start)
+    }
+
+    /// How many percentage points the illumination will change between today
+    /// and the `closest_phase`, negative while heading toward a new moon and
+    /// positive while heading toward a full moon. `None` if the closest phase
+    /// isn't a principal phase or has already passed, since the illumination
+    /// is then moving away from it.
+    pub fn illumination_delta(&self) -> Option<i16> {
+        if !self.closest_phase_is_future().ok()? {
+            return None;
+        }
+        let target = self.closest_phase.phase.principal_illumination()?;
+        Some(i16::from(target) - i16::from(self.percent_illuminated.0))
+    }
+
+    /// The `closest_phase` if it happens between `from` and `from + window`,
+    /// the phase time is taken to be in this day's `tz`. `None` if it falls
+    /// outside the window, the phase time is invalid or `from + window`
+    /// overflows.
+    pub fn phase_within(&self, window: time::Duration, from: OffsetDateTime) -> Option<MoonPhase> {
+        let when = self
+            .closest_phase
+            .when()
+            .ok()?
+            .assume_offset(self.utc_offset().ok()?);
+        let until = from.checked_add(window)?;
+        (from <= when && when <= until).then_some(self.closest_phase.phase)
+    }
+
+    /// An estimate of the illuminated percentage at `when`
+    ///
+    /// `percent_illuminated` is taken to be the value at the start of this day
+    /// and the illumination to change linearly toward the `closest_phase`'s
+    /// (0% new, 50% quarter, 100% full), which is good to a few percent within
+    /// a day or so of the requested date. `None` if the closest phase isn't a
+    /// principal phase or a date or `tz` is invalid.
+    pub fn illumination_at(&self, when: OffsetDateTime) -> Option<f32> {
+        let start = self.when().ok()?;
+        let current = f32::from(self.percent_illuminated.0);
+        let target = f32::from(self.closest_phase.phase.principal_illumination()?);
+        let closest = self
+            .closest_phase
+            .when()
+            .ok()?
+            .assume_offset(start.offset());
+        let span = (closest - start).as_seconds_f32();
+        if span == 0.0 {
+            return Some(current);
+        }
+        let elapsed = (when - start).as_seconds_f32();
+        Some((current + (target - current) * elapsed / span).clamp(0.0, 100.0))
+    }
+
+    /// If the sun stays above the horizon all day (midnight sun)
+    ///
+    /// With no sunrise or sunset the transit altitude decides. Without one
+    /// a day with no civil twilight could be midnight sun or deep polar
+    /// night, so this is `false`.
+    pub fn is_polar_day(&self) -> bool {
+        self.has_sun_events()
+            && self.sun_never_crosses_horizon()
+            && self
+                .sun_transit_altitude()
+                .is_some_and(|altitude| altitude > 0.0)
+    }
+
+    /// If the sun stays below the horizon all day
+    ///
+    /// With no sunrise or sunset the transit altitude decides, if the server
+    /// didn't provide one civil twilight means the sun stayed below the
+    /// horizon. Without either it could be midnight sun or deep polar night,
+    /// so this is `false`.
+    pub fn is_polar_night(&self) -> bool {
+        self.has_sun_events()
+            && self.sun_never_crosses_horizon()
+            && match self.sun_transit_altitude() {
+                Some(altitude) => altitude <= 0.0,
+                None => self.has_sun_twilight(),
+            }
+    }
+
+    /// `false` when the server sent no sun events at all, in which case the
+    /// sun helpers (polar checks, durations, windows) return `None` or nothing
+    /// rather than guessing
+    pub fn has_sun_events(&self) -> bool {
+        !self.sun_data.is_empty()
+    }
+
+    /// `false` when the server sent no moon events at all
+    pub fn has_moon_events(&self) -> bool {
+        !self.moon_data.is_empty()
+    }
+
+    fn sun_never_crosses_horizon(&self) -> bool {
+        !self
+            .sun_data
+            .iter()
+            .any(|e| matches!(e.phenomenon, Phenomenon::Rise | Phenomenon::Set))
+    }
+
+    fn has_sun_twilight(&self) -> bool {
+        self.sun_data.iter().any(|e| {
+            matches!(
+                e.phenomenon,
+                Phenomenon::TwilightBegins | Phenomenon::TwilightEnds
+            )
+        })
+    }
+
+    /// How the time of day of each sun and moon event moved from this day to
+    /// `other`, a positive delta means the event is later on `other`
+    pub fn diff(&self, other: &OneDayData) -> Result<EventDiff> {
+        Ok(EventDiff {
+            sun: BodyDiff::new(&self.sun_data, &other.sun_data)?,
+            moon: BodyDiff::new(&self.moon_data, &other.moon_data)?,
+        })
+    }
+
+    /// The sun's altitude, in degrees, at upper transit (the highest the sun
+    /// gets today) if the server included it.
+    pub fn sun_transit_altitude(&self) -> Option<f32> {
+        self.sun_event(Phenomenon::Apex)?.altitude
+    }
+
+    /// The moon's altitude, in degrees, at upper transit if the server
+    /// included it.
+    pub fn moon_transit_altitude(&self) -> Option<f32> {
+        self.moon_data
+            .iter()
+            .find(|e| e.phenomenon == Phenomenon::Apex)?
+            .altitude
+    }
+
+    /// A rough 0 to 1 score of how much the moon washes out the night sky,
+    /// 0 is a dark sky and 1 a full moon high overhead
+    ///
+    /// The score is the illuminated fraction scaled by the square root of the
+    /// sine of the moon's transit altitude, so a bright moon low on the horizon
+    /// counts for less while anything above ~45° counts for nearly all of it.
+    /// A moon that transits below the horizon scores 0. Without a transit
+    /// altitude the moon is assumed to get high enough to count fully. `None`
+    /// when the server sent no moon events.
+    pub fn moon_visibility_score(&self) -> Option<f32> {
+        if !self.has_moon_events() {
+            return None;
+        }
+        let illuminated = f32::from(self.percent_illuminated.0.min(100)) / 100.0;
+        let height = match self.moon_transit_altitude() {
+            Some(altitude) => altitude.to_radians().sin().max(0.0).sqrt(),
+            None => 1.0,
+        };
+        Some(illuminated * height)
+    }
+
+    /// The time between sunrise and sunset, `None` during polar day or night
+    /// or when either event is missing
+    pub fn daylight_duration(&self) -> Option<time::Duration> {
+        if self.is_polar_day() || self.is_polar_night() {
+            return None;
+        }
+        let rise = self.sun_event(Phenomenon::Rise)?.when().ok()?;
+        let set = self.sun_event(Phenomenon::Set)?.when().ok()?;
+        let duration = set - rise;
+        if duration.is_negative() {
+            // sunset is on the next calendar day in this timezone
+            return Some(duration + time::Duration::DAY);
+        }
+        Some(duration)
+    }
+
+    /// The time of the sun's upper transit, `None` during polar day or night
+    pub fn solar_noon(&self) -> Option<time::Time> {
+        if self.is_polar_day() || self.is_polar_night() {
+            return None;
+        }
+        self.sun_event(Phenomenon::Apex)?.when().ok()
+    }
+
+    fn sun_event(&self, phenomenon: Phenomenon) -> Option<&CelestialEvent> {
+        self.sun_data.iter().find(|e| e.phenomenon == phenomenon)
+    }
+
+    /// Every sun event followed by every moon event, with the event's instant
+    /// and its azimuth when the server provided one. Events whose instant
+    /// can't be built, because of an invalid date or `tz`, are left out.
+    pub fn events_with_geometry(&self) -> Vec<(Phenomenon, OffsetDateTime, Option<Azimuth>)> {
+        let Ok(start) = self.when() else {
+            return Vec::new();
+        };
+        self.sun_data
+            .iter()
+            .chain(&self.moon_data)
+            .filter_map(|event| {
+                let when = start.replace_time(event.when().ok()?);
+                Some((event.phenomenon, when, event.azimuth))
+            })
+            .collect()
+    }
+
+    /// The sun's events with sun specific helpers
+    pub fn sun(&self) -> SunData<'_> {
+        SunData {
+            events: &self.sun_data,
+        }
+    }
+
+    /// The golden hours after sunrise and before sunset, see
+    /// [`OneDayData::golden_hours_lasting`]
+    pub fn golden_hours(&self) -> Vec<(time::Time, time::Time)> {
+        self.golden_hours_lasting(GOLDEN_HOUR)
+    }
+
+    /// Windows of `duration` starting at sunrise and ending at sunset, the
+    /// soft light photographers call the golden hour. Empty during polar day
+    /// or night, a window is left out if its sunrise or sunset is missing.
+    pub fn golden_hours_lasting(&self, duration: time::Duration) -> Vec<(time::Time, time::Time)> {
+        if self.is_polar_day() || self.is_polar_night() {
+            return Vec::new();
+        }
+        self.sun().golden_hour_lasting(duration)
+    }
+
+    /// The blue hours between the start of civil twilight and sunrise and
+    /// between sunset and the end of civil twilight
+    ///
+    /// The blue hour runs into nautical twilight, which the API doesn't
+    /// report, so this is the civil part of it. Empty during polar day or
+    /// night, a window is left out if either of its events is missing.
+    pub fn blue_hours(&self) -> Vec<(time::Time, time::Time)> {
+        if self.is_polar_day() || self.is_polar_night() {
+            return Vec::new();
+        }
+        let sun = self.sun();
+        let twilight = |phenomenon| event_time(&self.sun_data, phenomenon);
+        let morning = twilight(Phenomenon::TwilightBegins).zip(sun.rise());
+        let evening = sun.set().zip(twilight(Phenomenon::TwilightEnds));
+        morning.into_iter().chain(evening).collect()
+    }
+
+    /// The moon's events with moon specific helpers
+    pub fn moon(&self) -> MoonData<'_> {
+        MoonData {
+            events: &self.moon_data,
+        }
+    }
+
+    /// A one line English summary for logs and notifications, e.g.
+    /// `Fri Apr 25 2025 — Waning Crescent (8%), sunrise 06:07, sunset 19:57,
+    /// moonrise 05:13, moonset 16:53`. Events the server didn't report are
+    /// left out. Fails if the date is invalid.
+    pub fn summary(&self) -> Result<String> {
+        let date = self.when()?.date();
+        let weekday = date.weekday().to_string();
+        let month = date.month().to_string();
+        let mut summary = format!(
+            "{} {} {} {} — {} ({}%)",
+            &weekday[..3],
+            &month[..3],
+            date.day(),
+            date.year(),
+            self.current_phase,
+            self.percent_illuminated.0
+        );
+        let (sun, moon) = (self.sun(), self.moon());
+        let events = [
+            ("sunrise", sun.rise()),
+            ("sunset", sun.set()),
+            ("moonrise", moon.rise()),
+            ("moonset", moon.set()),
+        ];
+        for (name, time) in events {
+            if let Some(time) = time {
+                summary.push_str(&format!(", {name} {:02}:{:02}", time.hour(), time.minute()));
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Render the sun and moon events as CSV with the header
+    /// `body,phenomenon,time`
+    pub fn events_to_csv(&self) -> String {
+        let mut csv = String::from("body,phenomenon,time\n");
+        let events = self
+            .sun_data
+            .iter()
+            .map(|e| ("sun", e))
+            .chain(self.moon_data.iter().map(|e| ("moon", e)));
+        for (body, event) in events {
+            csv.push_str(&format!(
+                "{body},{},{}\n",
+                csv_field(&event.phenomenon.to_string()),
+                event.time
+            ));
+        }
+        csv
+    }
+}
+
+/// How long the golden hour lasts after sunrise and before sunset
+const GOLDEN_HOUR: time::Duration = time::Duration::HOUR;
+
+/// The time of the first event in `events` for `phenomenon`
+fn event_time(events: &[CelestialEvent], phenomenon: Phenomenon) -> Option<time::Time> {
+    events
+        .iter()
+        .find(|e| e.phenomenon == phenomenon)?
+        .when()
+        .ok()
+}
+
+/// The sun's events for a day, see [`OneDayData::sun`]
+#[derive(Debug, Clone, Copy)]
+pub struct SunData<'a> {
+    events: &'a [CelestialEvent],
+}
+
+impl<'a> SunData<'a> {
+    pub fn events(&self) -> &'a [CelestialEvent] {
+        self.events
+    }
+
+    pub fn rise(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Rise)
+    }
+
+    pub fn set(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Set)
+    }
+
+    pub fn transit(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Apex)
+    }
+
+    /// The start of morning and end of evening civil twilight
+    pub fn civil_twilight(&self) -> Option<(time::Time, time::Time)> {
+        Some((
+            event_time(self.events, Phenomenon::TwilightBegins)?,
+            event_time(self.events, Phenomenon::TwilightEnds)?,
+        ))
+    }
+
+    /// The hour after sunrise and the hour before sunset, a window is left out
+    /// if its sunrise or sunset is missing
+    pub fn golden_hour(&self) -> Vec<(time::Time, time::Time)> {
+        self.golden_hour_lasting(GOLDEN_HOUR)
+    }
+
+    /// [`SunData::golden_hour`] with windows of `duration` instead of an hour
+    pub fn golden_hour_lasting(&self, duration: time::Duration) -> Vec<(time::Time, time::Time)> {
+        let morning = self.rise().map(|rise| (rise, rise + duration));
+        let evening = self.set().map(|set| (set - duration, set));
+        morning.into_iter().chain(evening).collect()
+    }
+}
+
+/// The moon's events for a day, see [`OneDayData::moon`]
+#[derive(Debug, Clone, Copy)]
+pub struct MoonData<'a> {
+    events: &'a [CelestialEvent],
+}
+
+impl<'a> MoonData<'a> {
+    pub fn events(&self) -> &'a [CelestialEvent] {
+        self.events
+    }
+
+    pub fn rise(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Rise)
+    }
+
+    pub fn set(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Set)
+    }
+
+    pub fn transit(&self) -> Option<time::Time> {
+        event_time(self.events, Phenomenon::Apex)
+    }
+}
+
+/// The sun half of a [`OneDayData`], see [`OneDayData::into_sun_only`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SunOnlyDay {
+    pub day_of_week: String,
+    pub sun_data: Vec<CelestialEvent>,
+    pub label: Option<String>,
+    month: u8,
+    day: u8,
+    year: u16,
+    tz: f32,
+}
+
+impl SunOnlyDay {
+    /// The start of this day in its timezone, see [`OneDayData::when`]
+    pub fn when(&self) -> Result<OffsetDateTime> {
+        start_of_day(self.year, self.month, self.day, self.tz)
+    }
+}
+
+/// Midnight on the given date at an offset of `tz` hours
+fn start_of_day(year: u16, month: u8, day: u8, tz: f32) -> Result<OffsetDateTime> {
+    let month =
+        time::Month::try_from(month).map_err(|e| anyhow::anyhow!("Invalid month in date: {e}"))?;
+    let dt = Date::from_calendar_date(year as _, month, day)
+        .map_err(|e| anyhow::anyhow!("invalid date: {e}"))?;
+    Ok(OffsetDateTime::new_in_offset(
+        dt,
+        time::Time::MIDNIGHT,
+        offset_from_hours(tz)?,
+    ))
+}
+
+/// The result of [`OneDayData::diff`]
+#[derive(Debug, Clone)]
+pub struct EventDiff {
+    pub sun: BodyDiff,
+    pub moon: BodyDiff,
+}
+
+/// The event time changes for one body between two days
+#[derive(Debug, Clone, Default)]
+pub struct BodyDiff {
+    /// Phenomena present on both days with the change in time of day
+    pub deltas: Vec<(Phenomenon, time::Duration)>,
+    /// Phenomena that only happened on the first day
+    pub only_in_self: Vec<Phenomenon>,
+    /// Phenomena that only happened on the second day
+    pub only_in_other: Vec<Phenomenon>,
+}
+
+impl BodyDiff {
+    fn new(lhs: &[CelestialEvent], rhs: &[CelestialEvent]) -> Result<Self> {
+        let mut ret = Self::default();
+        let mut unmatched: Vec<&CelestialEvent> = rhs.iter().collect();
+        for event in lhs {
+            let Some(idx) = unmatched
+                .iter()
+                .position(|e| e.phenomenon == event.phenomenon)
+            else {
+                ret.only_in_self.push(event.phenomenon);
+                continue;
+            };
+            let other = unmatched.remove(idx);
+            ret.deltas
+                .push((event.phenomenon, other.when()? - event.when()?));
+        }
+        ret.only_in_other = unmatched.into_iter().map(|e| e.phenomenon).collect();
+        Ok(ret)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ClosestPhase {
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    day: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    month: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    year: u16,
+    #[serde(deserialize_with = "deser_time")]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "schema::time"))]
+    time: Time,
+    pub phase: MoonPhase,
+}
+
+impl ClosestPhase {
+    pub fn when(&self) -> Result<PrimitiveDateTime> {
+        let month = time::Month::try_from(self.month).map_err(|e| {
+            anyhow::anyhow!("Invalid month in date: {e}")
+        })?;
+        let dt = Date::from_calendar_date(self.year as _, month, self.day).map_err(|e| {
+            anyhow::anyhow!("invalid date: {e}")
+        })?;
+        let t = self.time.to_time()?;
+        Ok(PrimitiveDateTime::new(dt, t))
+    }
+}
+
+/// A value from the API the crate doesn't recognize, see
+/// [`MoonPhase::Unknown`] and [`Phenomenon::Unknown`]
+///
+/// The text is stored inline so the enums holding it stay `Copy`, anything
+/// longer than [`UnknownValue::CAPACITY`] bytes is cut short at a character
+/// boundary.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownValue {
+    len: u8,
+    bytes: [u8; UnknownValue::CAPACITY],
+}
+
+impl UnknownValue {
+    /// The most bytes of the original text that are kept
+    pub const CAPACITY: usize = 32;
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)])
+            .expect("unknown values are cut on a character boundary")
+    }
+}
+
+impl From<&str> for UnknownValue {
+    fn from(value: &str) -> Self {
+        let mut len = value.len().min(Self::CAPACITY);
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut bytes = [0; Self::CAPACITY];
+        bytes[..len].copy_from_slice(&value.as_bytes()[..len]);
+        Self {
+            len: len as u8,
+            bytes,
+        }
+    }
+}
+
+impl PartialOrd for UnknownValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UnknownValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl std::fmt::Debug for UnknownValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl std::fmt::Display for UnknownValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A phase of the moon
+///
+/// Values the crate doesn't recognize are kept in `Unknown` so a new phase
+/// name from the API doesn't fail the whole response, enable the `strict`
+/// feature to make them an error instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+    Unknown(UnknownValue),
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for MoonPhase {
+    fn schema_name() -> String {
+        "MoonPhase".into()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schema::known_names(
+            &MoonPhase::all()
+                .map(|phase| serde_json::to_value(phase).expect("phases serialize to strings")),
+        )
+    }
+}
+
+impl MoonPhase {
+    const ALL: [MoonPhase; 8] = [
+        Self::New,
+        Self::WaxingCrescent,
+        Self::FirstQuarter,
+        Self::WaxingGibbous,
+        Self::Full,
+        Self::WaningGibbous,
+        Self::LastQuarter,
+        Self::WaningCrescent,
+    ];
+
+    /// Every known phase in cycle order, `Unknown` is not included
+    pub fn all() -> [MoonPhase; 8] {
+        Self::ALL
+    }
+
+    /// The position of this phase in the lunar cycle, 0 for `New` through 7
+    /// for `WaningCrescent`, `None` for `Unknown`
+    pub fn as_index(&self) -> Option<u8> {
+        Some(match self {
+            Self::New => 0,
+            Self::WaxingCrescent => 1,
+            Self::FirstQuarter => 2,
+            Self::WaxingGibbous => 3,
+            Self::Full => 4,
+            Self::WaningGibbous => 5,
+            Self::LastQuarter => 6,
+            Self::WaningCrescent => 7,
+            Self::Unknown(_) => return None,
+        })
+    }
+
+    /// The inverse of [`MoonPhase::as_index`], `None` for anything above 7
+    pub fn from_index(index: u8) -> Option<Self> {
+        Some(match index {
+            0 => Self::New,
+            1 => Self::WaxingCrescent,
+            2 => Self::FirstQuarter,
+            3 => Self::WaxingGibbous,
+            4 => Self::Full,
+            5 => Self::WaningGibbous,
+            6 => Self::LastQuarter,
+            7 => Self::WaningCrescent,
+            _ => return None,
+        })
+    }
+
+    /// A plain ASCII picture of this phase for logs and terminals that render
+    /// emoji poorly
+    ///
+    /// Parentheses stand for the moon's limbs with the lit part drawn as seen
+    /// from the northern hemisphere, lit on the right while waxing and on the
+    /// left while waning: `( )` New, `(|` waxing crescent, `(D` First
+    /// Quarter, `(0` waxing gibbous, `(O)` Full, `0)` waning gibbous, `C)`
+    /// Last Quarter and `|)` waning crescent. Unknown phases are `(?)`.
+    pub fn symbol_ascii(&self) -> &'static str {
+        match self {
+            Self::New => "( )",
+            Self::WaxingCrescent => "(|",
+            Self::FirstQuarter => "(D",
+            Self::WaxingGibbous => "(0",
+            Self::Full => "(O)",
+            Self::WaningGibbous => "0)",
+            Self::LastQuarter => "C)",
+            Self::WaningCrescent => "|)",
+            Self::Unknown(_) => "(?)",
+        }
+    }
+
+    /// `true` for the four principal phases: `New`, `FirstQuarter`, `Full` and
+    /// `LastQuarter`
+    pub fn is_principal(&self) -> bool {
+        matches!(
+            self,
+            Self::New | Self::FirstQuarter | Self::Full | Self::LastQuarter
+        )
+    }
+
+    /// `true` for the four intermediate phases: `WaxingCrescent`,
+    /// `WaxingGibbous`, `WaningGibbous` and `WaningCrescent`
+    pub fn is_intermediate(&self) -> bool {
+        matches!(
+            self,
+            Self::WaxingCrescent | Self::WaxingGibbous | Self::WaningGibbous | Self::WaningCrescent
+        )
+    }
+
+    /// The illuminated percentage at the instant of a principal phase, `None`
+    /// for the other phases since they span a range
+    fn principal_illumination(&self) -> Option<u8> {
+        match self {
+            Self::New => Some(0),
+            Self::FirstQuarter | Self::LastQuarter => Some(50),
+            Self::Full => Some(100),
+            _ => None,
+        }
+    }
+
+    /// A generous range of illuminated percentages for a day labelled with
+    /// this phase, `None` for unknown phases
+    fn plausible_illumination(&self) -> Option<RangeInclusive<u8>> {
+        Some(match self {
+            Self::New => 0..=15,
+            Self::WaxingCrescent | Self::WaningCrescent => 0..=60,
+            Self::FirstQuarter | Self::LastQuarter => 35..=65,
+            Self::WaxingGibbous | Self::WaningGibbous => 40..=100,
+            Self::Full => 85..=100,
+            _ => return None,
+        })
+    }
+
+    /// The nominal age of the moon, in days since the new moon, for this
+    /// phase, `None` for unknown phases
+    ///
+    /// The eight phases are spaced evenly across a mean synodic month of
+    /// ~29.53 days: `New` 0, `FirstQuarter` ~7.4, `Full` ~14.8 and
+    /// `LastQuarter` ~22.1 with the intermediate phases halfway between. A
+    /// real lunation runs several hours longer or shorter than the mean.
+    pub fn typical_age_days(&self) -> Option<f32> {
+        self.as_index()
+            .map(|index| index as f32 * SYNODIC_MONTH_DAYS / 8.0)
+    }
+
+    /// The phase whose [`MoonPhase::typical_age_days`] is closest to `age`,
+    /// which is wrapped into a mean synodic month first so an age just short
+    /// of a full month is `New`
+    pub fn from_age_days(age: f32) -> Self {
+        let step = SYNODIC_MONTH_DAYS / 8.0;
+        let index = (age.rem_euclid(SYNODIC_MONTH_DAYS) / step).round() as u8 % 8;
+        Self::from_index(index).unwrap_or(Self::New)
+    }
+
+    fn from_wire(value: &str) -> Option<Self> {
+        Some(match value {
+            "New" | wire::NEW_MOON => Self::New,
+            "WaxingCrescent" | wire::WAXING_CRESCENT => Self::WaxingCrescent,
+            "FirstQuarter" | wire::FIRST_QUARTER => Self::FirstQuarter,
+            "WaxingGibbous" | wire::WAXING_GIBBOUS => Self::WaxingGibbous,
+            "Full" | wire::FULL_MOON => Self::Full,
+            "WaningGibbous" | wire::WANING_GIBBOUS => Self::WaningGibbous,
+            "LastQuarter" | wire::LAST_QUARTER => Self::LastQuarter,
+            "WaningCrescent" | wire::WANING_CRESCENT => Self::WaningCrescent,
+            _ => return None,
+        })
+    }
+}
+
+impl Serialize for MoonPhase {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(match self {
+            Self::New => "New",
+            Self::WaxingCrescent => "WaxingCrescent",
+            Self::FirstQuarter => "FirstQuarter",
+            Self::WaxingGibbous => "WaxingGibbous",
+            Self::Full => "Full",
+            Self::WaningGibbous => "WaningGibbous",
+            Self::LastQuarter => "LastQuarter",
+            Self::WaningCrescent => "WaningCrescent",
+            Self::Unknown(value) => value.as_str(),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for MoonPhase {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deser_lenient(d, Self::from_wire, Self::Unknown)
+    }
+}
+
+/// (De)serialize a [`MoonPhase`] as its numeric index for use with
+/// `#[serde(with = "moon_unit::phase_index")]`
+pub mod phase_index {
+    use super::MoonPhase;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(phase: &MoonPhase, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let index = phase
+            .as_index()
+            .ok_or_else(|| serde::ser::Error::custom(format!("{phase} has no phase index")))?;
+        s.serialize_u8(index)
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<MoonPhase, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let index = u8::deserialize(d)?;
+        MoonPhase::from_index(index)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid phase index: {index}")))
+    }
+}
+
+impl std::fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::New => wire::NEW_MOON,
+            Self::WaxingCrescent => wire::WAXING_CRESCENT,
+            Self::FirstQuarter => wire::FIRST_QUARTER,
+            Self::WaxingGibbous => wire::WAXING_GIBBOUS,
+            Self::Full => wire::FULL_MOON,
+            Self::WaningGibbous => wire::WANING_GIBBOUS,
+            Self::LastQuarter => wire::LAST_QUARTER,
+            Self::WaningCrescent => wire::WANING_CRESCENT,
+            Self::Unknown(value) => value.as_str(),
+        })
+    }
+}
+
+/// An event in the sun or moon's daily path
+///
+/// Like [`MoonPhase`], unrecognized values are kept in `Unknown` unless the
+/// `strict` feature is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phenomenon {
+    Rise,
+    Apex,
+    TwilightBegins,
+    Set,
+    TwilightEnds,
+    Unknown(UnknownValue),
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Phenomenon {
+    fn schema_name() -> String {
+        "Phenomenon".into()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schema::known_names(
+            &Phenomenon::all()
+                .iter()
+                .map(|phenomenon| {
+                    serde_json::to_value(phenomenon).expect("phenomena serialize to strings")
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl Phenomenon {
+    const ALL: &'static [Phenomenon] = &[
+        Self::TwilightBegins,
+        Self::Rise,
+        Self::Apex,
+        Self::Set,
+        Self::TwilightEnds,
+    ];
+
+    /// Every known phenomenon in the order they occur through a day, `Unknown`
+    /// is not included
+    pub fn all() -> &'static [Phenomenon] {
+        Self::ALL
+    }
+
+    /// A rough compass direction for where this event happens in the sky as
+    /// seen from latitude `lat`: east for a rise, west for a set and, for the
+    /// transit, south in the northern hemisphere or north in the southern
+    /// one. Twilight and unknown events have no direction.
+    ///
+    /// This is only a hint for labeling, the true azimuth swings far from
+    /// due east and west through the year and at high latitudes, and in the
+    /// tropics the transit can be on either side.
+    pub fn rough_cardinal(&self, lat: f32) -> Option<CardinalDirection> {
+        match self {
+            Self::Rise => Some(CardinalDirection::East),
+            Self::Set => Some(CardinalDirection::West),
+            Self::Apex if lat < 0.0 => Some(CardinalDirection::North),
+            Self::Apex => Some(CardinalDirection::South),
+            Self::TwilightBegins | Self::TwilightEnds | Self::Unknown(_) => None,
+        }
+    }
+
+    fn from_wire(value: &str) -> Option<Self> {
+        Some(match value {
+            wire::RISE => Self::Rise,
+            "Apex" | wire::UPPER_TRANSIT => Self::Apex,
+            "TwilightBegins" | wire::BEGIN_CIVIL_TWILIGHT => Self::TwilightBegins,
+            wire::SET => Self::Set,
+            "TwilightEnds" | wire::END_CIVIL_TWILIGHT => Self::TwilightEnds,
+            _ => return None,
+        })
+    }
+}
+
+impl Serialize for Phenomenon {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(match self {
+            Self::Rise => "Rise",
+            Self::Apex => "Apex",
+            Self::TwilightBegins => "TwilightBegins",
+            Self::Set => "Set",
+            Self::TwilightEnds => "TwilightEnds",
+            Self::Unknown(value) => value.as_str(),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Phenomenon {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deser_lenient(d, Self::from_wire, Self::Unknown)
+    }
+}
+
+impl std::fmt::Display for Phenomenon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Rise => wire::RISE,
+            Self::Apex => wire::UPPER_TRANSIT,
+            Self::TwilightBegins => wire::BEGIN_CIVIL_TWILIGHT,
+            Self::Set => wire::SET,
+            Self::TwilightEnds => wire::END_CIVIL_TWILIGHT,
+            Self::Unknown(value) => value.as_str(),
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CelestialEvent {
+    #[serde(alias = "phen")]
+    pub phenomenon: Phenomenon,
+    #[serde(deserialize_with = "deser_time")]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "schema::time"))]
+    time: Time,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f32>,
+    /// The compass bearing of the body at this event, `None` when absent or
+    /// not a number
+    #[serde(
+        default,
+        deserialize_with = "lenient_azimuth",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub azimuth: Option<Azimuth>,
+}
+
+/// One of the four cardinal compass directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardinalDirection {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// A compass bearing in degrees clockwise from true north, `0.0..360.0`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct Azimuth(f32);
+
+impl Azimuth {
+    /// Wraps `degrees` into `0.0..360.0`, `None` if it isn't finite
+    pub fn new(degrees: f32) -> Option<Self> {
+        degrees.is_finite().then(|| Self(degrees.rem_euclid(360.0)))
+    }
+
+    pub fn degrees(&self) -> f32 {
+        self.0
+    }
+}
+
+/// Deserialize an azimuth from a number or numeric string, anything else is
+/// treated as missing rather than failing the response
+fn lenient_azimuth<'de, D>(d: D) -> Result<Option<Azimuth>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f32),
+        Text(String),
+        Other(serde::de::IgnoredAny),
+    }
+    Ok(match Raw::deserialize(d)? {
+        Raw::Number(degrees) => Azimuth::new(degrees),
+        Raw::Text(text) => parse_float(&text).and_then(|degrees| Azimuth::new(degrees as f32)),
+        Raw::Other(_) => None,
+    })
+}
+
+impl std::fmt::Debug for CelestialEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CelestialEvent")
+            .field("phenomenon", &self.phenomenon)
+            .field("time", &format_args!("{}", self.time))
+            .field("altitude", &self.altitude)
+            .field("azimuth", &self.azimuth)
+            .finish()
+    }
+}
+
+impl CelestialEvent {
+    pub fn when(&self) -> Result<time::Time> {
+        self.time.to_time()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+struct Time {
+    hour: u8,
+    minute: u8,
+    /// Only present in the response for some requests, 0 when absent
+    #[serde(default, skip_serializing_if = "is_zero")]
+    second: u8,
+}
+
+impl Time {
+    fn to_time(&self) -> Result<time::Time> {
+        time::Time::from_hms(self.hour, self.minute, self.second)
+            .map_err(|e| anyhow::anyhow!("invalid time: {e}"))
+    }
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)?;
+        if self.second != 0 {
+            write!(f, ":{:02}", self.second)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_zero(v: &u8) -> bool {
+    *v == 0
+}
+
+/// The start of `body` with runs of whitespace collapsed, for error messages
+fn snippet(body: &[u8]) -> String {
+    const MAX: usize = 100;
+    let text = String::from_utf8_lossy(body);
+    let mut snippet = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((end, _)) = snippet.char_indices().nth(MAX) {
+        snippet.truncate(end);
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// The inverse of [`offset_from_hours`], fails if `offset` has a seconds
+/// component
+fn hours_from_offset(offset: time::UtcOffset) -> Result<f32> {
+    let (hours, minutes, seconds) = offset.as_hms();
+    if seconds != 0 {
+        anyhow::bail!(
+            "UTC offset {offset} has a seconds component, only whole minutes are supported"
+        )
+    }
+    Ok(f32::from(hours) + f32::from(minutes) / 60.0)
+}
+
+/// The offset in fractional hours of the IANA zone `zone` on `date`, taken at
+/// local noon so the day's DST transition (usually overnight) has happened
+#[cfg(feature = "tz-iana")]
+fn iana_offset_hours(zone: &str, date: Date) -> Result<f32> {
+    use time_tz::{Offset, PrimitiveDateTimeExt, TimeZone};
+    let tz = time_tz::timezones::get_by_name(zone)
+        .ok_or_else(|| anyhow::anyhow!("unknown IANA time zone: {zone:?}"))?;
+    let offset = match PrimitiveDateTime::new(date, time::Time::from_hms(12, 0, 0)?)
+        .assume_timezone(tz)
+        .take_first()
+    {
+        Some(local) => local.offset(),
+        None => tz.get_offset_utc(&date.midnight().assume_utc()).to_utc(),
+    };
+    hours_from_offset(offset)
+}
+
+/// Convert an offset in fractional hours, as the API uses, to a
+/// [`time::UtcOffset`] rounded to the nearest minute
+///
+/// The hour and minute share the sign of `hours`, so `-3.5` is `-03:30`.
+fn offset_from_hours(hours: f32) -> Result<time::UtcOffset> {
+    // `UtcOffset` tops out just short of 26 hours, checking first also keeps
+    // the conversion below from overflowing
+    if !hours.is_finite() || hours.abs() >= 26.0 {
+        anyhow::bail!("invalid UTC offset: {hours}")
+    }
+    let minutes = (hours * 60.0).round() as i32;
+    time::UtcOffset::from_whole_seconds(minutes * 60)
+        .map_err(|e| anyhow::anyhow!("invalid UTC offset {hours}: {e}"))
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+/// Parse a decimal number, accepting `,` as well as `.` as the decimal
+/// separator
+///
+/// The API always uses `.`, this is a leniency for data that has passed
+/// through a localizing proxy or reformatting layer.
+fn parse_float(v: &str) -> Option<f64> {
+    let v = v.trim();
+    v.parse()
+        .ok()
+        .or_else(|| v.replacen(',', ".", 1).parse().ok())
+        .filter(|v: &f64| v.is_finite())
+}
+
+/// Deserialize a float that may be a JSON number or a string, see
+/// [`parse_float`]
+fn lenient_f32<'de, D>(d: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LenientFloatVisitor;
+    impl serde::de::Visitor<'_> for LenientFloatVisitor {
+        type Value = f32;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a number or a string containing a number")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            parse_float(v)
+                .map(|v| v as f32)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid number {v:?}")))
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as f32)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as f32)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as f32)
+        }
+    }
+    d.deserialize_any(LenientFloatVisitor)
+}
+
+/// A point's coordinates, or nothing for the nested arrays of other
+/// geometries
+fn lenient_coordinates<'de, D>(d: D) -> Result<Vec<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Coordinates {
+        Point(Vec<f32>),
+        Other(serde::de::IgnoredAny),
+    }
+    Ok(match Coordinates::deserialize(d)? {
+        Coordinates::Point(coordinates) => coordinates,
+        Coordinates::Other(_) => Vec::new(),
+    })
+}
+
+/// Deserialize an optional boolean, accepting `"true"`/`"false"` and `1`/`0`,
+/// anything else is treated as missing rather than failing the response
+fn lenient_bool<'de, D>(d: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LenientBoolVisitor;
+    impl<'de> serde::de::Visitor<'de> for LenientBoolVisitor {
+        type Value = Option<bool>;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a boolean")
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(Some(v))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(match v.trim() {
+                v if v.eq_ignore_ascii_case("true") => Some(true),
+                v if v.eq_ignore_ascii_case("false") => Some(false),
+                _ => None,
+            })
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(match v {
+                0 => Some(false),
+                1 => Some(true),
+                _ => None,
+            })
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            match u64::try_from(v) {
+                Ok(v) => self.visit_u64(v),
+                Err(_) => Ok(None),
+            }
+        }
+
+        fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            while map
+                .next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?
+                .is_some()
+            {}
+            Ok(None)
+        }
+    }
+    d.deserialize_any(LenientBoolVisitor)
+}
+
+/// Deserialize a number that the API may send either as a JSON number or a
+/// quoted string
+fn string_or_number<'de, D, T>(d: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr + TryFrom<u64>,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+    <T as TryFrom<u64>>::Error: std::fmt::Display,
+{
+    struct StringOrNumberVisitor<T>(std::marker::PhantomData<T>);
+    impl<T> serde::de::Visitor<'_> for StringOrNumberVisitor<T>
+    where
+        T: std::str::FromStr + TryFrom<u64>,
+        <T as std::str::FromStr>::Err: std::fmt::Display,
+        <T as TryFrom<u64>>::Error: std::fmt::Display,
+    {
+        type Value = T;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a number or a string containing a number")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            v.trim()
+                .parse()
+                .map_err(|e| serde::de::Error::custom(format!("invalid number {v:?}: {e}")))
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            T::try_from(v).map_err(|e| serde::de::Error::custom(format!("invalid number {v}: {e}")))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            let v = u64::try_from(v)
+                .map_err(|e| serde::de::Error::custom(format!("invalid number {v}: {e}")))?;
+            self.visit_u64(v)
+        }
+    }
+    d.deserialize_any(StringOrNumberVisitor(std::marker::PhantomData))
+}
+
+/// Deserialize a string with `known`, falling back to `unknown` for
+/// unrecognized values unless the `strict` feature is enabled
+fn deser_lenient<'de, D, T>(
+    d: D,
+    known: impl Fn(&str) -> Option<T>,
+    unknown: impl FnOnce(UnknownValue) -> T,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(d)?;
+    match known(&value) {
+        Some(known) => Ok(known),
+        None if cfg!(feature = "strict") => Err(serde::de::Error::custom(format!(
+            "unknown variant: {value:?}"
+        ))),
+        None => Ok(unknown(UnknownValue::from(value.as_str()))),
+    }
+}
+
+/// The illuminated fraction of the moon's disk as a whole percentage
+///
+/// The API reports this as a string like `"8%"`, this also accepts plain
+/// numbers so serialized values round trip. Fractional values, with either `.`
+/// or `,` as the decimal separator, are rounded to the nearest percent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Illumination(pub u8);
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Illumination {
+    fn schema_name() -> String {
+        "Illumination".into()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schema::from_json(serde_json::json!({
+            "description": "The illuminated percentage of the moon's disk",
+            "anyOf": [
+                { "type": "integer", "minimum": 0, "maximum": 100 },
+                { "type": "string", "pattern": "^\\s*\\d{1,3}([.,]\\d*)?\\s*%?\\s*$" },
+            ],
+        }))
+    }
+}
+
+impl From<Illumination> for u8 {
+    fn from(value: Illumination) -> Self {
+        value.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Illumination {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct IlluminationVisitor;
+        impl serde::de::Visitor<'_> for IlluminationVisitor {
+            type Value = Illumination;
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("number and percent")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let value = parse_float(v.trim().trim_end_matches('%')).ok_or_else(|| {
+                    serde::de::Error::custom(format!("Failed to parse percent: {v:?}"))
+                })?;
+                self.visit_f64(value)
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if !(v.is_finite() && v >= 0.0 && v.round() <= 100.0) {
+                    return Err(serde::de::Error::custom(format!(
+                        "percent out of range 0-100: {v}"
+                    )));
+                }
+                Ok(Illumination(v.round() as u8))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match u8::try_from(v) {
+                    Ok(v) if v <= 100 => Ok(Illumination(v)),
+                    _ => Err(serde::de::Error::custom(format!(
+                        "percent out of range 0-100: {v}"
+                    ))),
+                }
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let v = u64::try_from(v).map_err(|_| {
+                    serde::de::Error::custom(format!("percent out of range 0-100: {v}"))
+                })?;
+                self.visit_u64(v)
+            }
+        }
+        d.deserialize_any(IlluminationVisitor)
+    }
+}
+
+fn deser_time<'de, D>(d: D) -> Result<Time, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct TimeVisitor;
+    impl<'de> serde::de::Visitor<'de> for TimeVisitor {
+        type Value = Time;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str(r"time with the format \d{2}:\d{2} or \d{2}:\d{2}:\d{2}")
+        }
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            let (hours, minutes) = v
+                .split_once(":")
+                .ok_or_else(|| serde::de::Error::custom(format!("time missing colon: {v:?}")))?;
+            let (minutes, seconds) = minutes.split_once(":").unwrap_or((minutes, "0"));
+            Ok(Time {
+                hour: hours
+                    .parse()
+                    .map_err(|e| serde::de::Error::custom(format!("invalid hour-{e}: {v:?}")))?,
+                minute: minutes
+                    .parse()
+                    .map_err(|e| serde::de::Error::custom(format!("invalid minute-{e}: {v:?}")))?,
+                second: seconds
+                    .parse()
+                    .map_err(|e| serde::de::Error::custom(format!("invalid second-{e}: {v:?}")))?,
+            })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut hour = None;
+            let mut minute = None;
+            let mut second = 0;
+            while let Some(key) = map.next_key::<&str>()? {
+                match key {
+                    "hour" => {
+                        hour = Some(map.next_value::<u8>()?);
+                    }
+                    "minute" => {
+                        minute = Some(map.next_value::<u8>()?);
+                    }
+                    "second" => {
+                        second = map.next_value::<u8>()?;
+                    }
+                    _ => {}
+                }
+            }
+            let hour = hour.ok_or_else(|| serde::de::Error::custom("hour missing from map"))?;
+            let minute =
+                minute.ok_or_else(|| serde::de::Error::custom("minute missing from map"))?;
+            Ok(Time {
+                hour,
+                minute,
+                second,
+            })
+        }
+    }
+    d.deserialize_any(TimeVisitor)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MoonPhasesResponse {
+    #[serde(alias = "numphases")]
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    pub count: u16,
+    #[serde(alias = "phasedata")]
+    pub phases: Vec<MoonPhaseEntry>,
+    /// The offset, in hours, the phase times are reported in, `None` for UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz: Option<f32>,
+    #[serde(skip)]
+    from_cache: bool,
+    #[serde(skip)]
+    fetched_at: Option<OffsetDateTime>,
+}
+
+impl MoonPhasesResponse {
+    /// `true` when this response was served from the client's conditional
+    /// cache after revalidating with the server, or from its disk cache
+    pub fn from_cache(&self) -> bool {
+        self.from_cache
+    }
+
+    /// When the [`Client`] received this response, `None` if it was
+    /// deserialized some other way. For a response read from the disk cache
+    /// this is when the cache file was written.
+    pub fn fetched_at(&self) -> Option<OffsetDateTime> {
+        self.fetched_at
+    }
+
+    /// Sort the phases by date and time and drop any duplicate entries,
+    /// `count` is updated to match.
+    fn normalize(&mut self) {
+        self.phases.sort_by_key(MoonPhaseEntry::sort_key);
+        self.phases
+            .dedup_by(|a, b| a.phase == b.phase && a.sort_key() == b.sort_key());
+        self.count = self.phases.len() as u16;
+    }
+
+    /// The offset the phase times are reported in, UTC unless the request
+    /// had a `tz`. Fails if `tz` is out of range.
+    pub fn utc_offset(&self) -> Result<time::UtcOffset> {
+        self.tz.map_or(Ok(time::UtcOffset::UTC), offset_from_hours)
+    }
+
+    /// The instant of `entry`, one of this response's phases, taking the
+    /// response's `tz` into account
+    pub fn instant(&self, entry: &MoonPhaseEntry) -> Result<OffsetDateTime> {
+        Ok(entry.when()?.assume_offset(self.utc_offset()?))
+    }
+
+    /// [`MoonPhasesResponse::instant`] as a UTC date and time, for comparing
+    /// with the UTC arguments of the helpers below
+    fn utc_when(&self, entry: &MoonPhaseEntry) -> Option<PrimitiveDateTime> {
+        let utc = self.instant(entry).ok()?.to_offset(time::UtcOffset::UTC);
+        Some(PrimitiveDateTime::new(utc.date(), utc.time()))
+    }
+
+    /// `true` if every phase is before `today`, an empty response is never
+    /// settled
+    #[cfg(feature = "disk-cache")]
+    fn settled_before(&self, today: Date) -> bool {
+        !self.phases.is_empty()
+            && self
+                .phases
+                .iter()
+                .all(|entry| self.utc_when(entry).is_some_and(|when| when.date() < today))
+    }
+
+    /// Render the phases as CSV with the header `date,time,phase`
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,time,phase\n");
+        for entry in &self.phases {
+            csv.push_str(&format!(
+                "{:04}-{:02}-{:02},{},{}\n",
+                entry.year,
+                entry.month,
+                entry.day,
+                entry.time,
+                csv_field(&entry.phase.to_string())
+            ));
+        }
+        csv
+    }
+
+    /// The phases bucketed by `(year, month)`, entries keep their original
+    /// order within each bucket
+    pub fn group_by_month(&self) -> BTreeMap<(u16, u8), Vec<&MoonPhaseEntry>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for entry in &self.phases {
+            groups
+                .entry((entry.year, entry.month))
+                .or_default()
+                .push(entry);
+        }
+        groups
+    }
+
+    /// Each entry paired with the time from `now` until it occurs, negative
+    /// for entries that have passed. Entries with an invalid date or time are
+    /// left out. `now` is in UTC whatever `tz` the entries are in.
+    pub fn with_countdowns(&self, now: PrimitiveDateTime) -> Vec<(MoonPhaseEntry, time::Duration)> {
+        self.phases
+            .iter()
+            .filter_map(|entry| Some((entry.clone(), self.utc_when(entry)? - now)))
+            .collect()
+    }
+
+    /// An estimate of the illuminated percentage at `date` (UTC, whatever
+    /// `tz` the entries are in) from the New and Full Moon entries on either
+    /// side of it
+    ///
+    /// Illumination is taken to follow half a cosine wave from 0% at New Moon
+    /// to 100% at Full Moon and back, which ignores the moon's uneven orbital
+    /// speed, expect it to be off by a few percent near the quarters. `None`
+    /// when `date` is before the first or after the last New/Full entry or
+    /// the surrounding entries aren't one New and one Full (e.g. a gap in the
+    /// data).
+    pub fn estimated_illumination(&self, date: PrimitiveDateTime) -> Option<u8> {
+        let mut anchors: Vec<(PrimitiveDateTime, &MoonPhase)> = self
+            .phases
+            .iter()
+            .filter(|entry| matches!(entry.phase, MoonPhase::New | MoonPhase::Full))
+            .filter_map(|entry| Some((self.utc_when(entry)?, &entry.phase)))
+            .collect();
+        anchors.sort_by_key(|(when, _)| *when);
+        let (start, end) = anchors
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|((start, _), (end, _))| (*start..=*end).contains(&date))?;
+        if start.1 == end.1 {
+            return None;
+        }
+        let span = (end.0 - start.0).as_seconds_f64();
+        let progress = if span > 0.0 {
+            (date - start.0).as_seconds_f64() / span
+        } else {
+            0.0
+        };
+        let waxing = (1.0 - (std::f64::consts::PI * progress).cos()) / 2.0;
+        let fraction = if *start.1 == MoonPhase::New {
+            waxing
+        } else {
+            1.0 - waxing
+        };
+        Some((fraction * 100.0).round() as u8)
+    }
+
+    /// The first `target` entry at or after `from` with the closest `neighbor`
+    /// entries on either side of it, `None` if any of them isn't in this
+    /// response. `from` is in UTC whatever `tz` the entries are in.
+    pub fn neighbors(
+        &self,
+        from: PrimitiveDateTime,
+        target: &MoonPhase,
+        neighbor: &MoonPhase,
+    ) -> Option<PhaseNeighbors> {
+        let index = self.phases.iter().position(|entry| {
+            entry.phase == *target && self.utc_when(entry).is_some_and(|when| when >= from)
+        })?;
+        let previous = self.phases[..index]
+            .iter()
+            .rev()
+            .find(|entry| entry.phase == *neighbor)?;
+        let next = self.phases[index + 1..]
+            .iter()
+            .find(|entry| entry.phase == *neighbor)?;
+        Some(PhaseNeighbors {
+            previous: previous.clone(),
+            target: self.phases[index].clone(),
+            next: next.clone(),
+        })
+    }
+
+    /// The principal phases grouped into lunations, each starting at a new
+    /// moon, in order
+    ///
+    /// Cycles cut off by the start or end of the response are partial and
+    /// have `None` for their missing phases, as does a cycle where the data
+    /// skips a phase. Intermediate and unknown phases are ignored.
+    pub fn lunations(&self) -> Vec<Lunation> {
+        let mut lunations: Vec<Lunation> = Vec::new();
+        let mut last_index = None;
+        for entry in &self.phases {
+            let Some(index) = entry
+                .phase
+                .as_index()
+                .filter(|_| entry.phase.is_principal())
+            else {
+                continue;
+            };
+            if entry.phase == MoonPhase::New || last_index.is_none_or(|last| index <= last) {
+                lunations.push(Lunation::default());
+            }
+            last_index = Some(index);
+            let lunation = lunations.last_mut().expect("pushed above");
+            let slot = match entry.phase {
+                MoonPhase::New => &mut lunation.new_moon,
+                MoonPhase::FirstQuarter => &mut lunation.first_quarter,
+                MoonPhase::Full => &mut lunation.full_moon,
+                _ => &mut lunation.last_quarter,
+            };
+            *slot = Some(entry.clone());
+        }
+        lunations
+    }
+
+    /// The entries for the principal phases (see [`MoonPhase::is_principal`])
+    pub fn principal_only(&self) -> Vec<&MoonPhaseEntry> {
+        self.phases
+            .iter()
+            .filter(|entry| entry.phase.is_principal())
+            .collect()
+    }
+
+    /// The entries for the intermediate phases (see
+    /// [`MoonPhase::is_intermediate`]), `Unknown` phases are in neither list
+    pub fn intermediate_only(&self) -> Vec<&MoonPhaseEntry> {
+        self.phases
+            .iter()
+            .filter(|entry| entry.phase.is_intermediate())
+            .collect()
+    }
+}
+
+/// The four principal phases of one cycle, see [`MoonPhasesResponse::lunations`]
+#[derive(Debug, Clone, Default)]
+pub struct Lunation {
+    pub new_moon: Option<MoonPhaseEntry>,
+    pub first_quarter: Option<MoonPhaseEntry>,
+    pub full_moon: Option<MoonPhaseEntry>,
+    pub last_quarter: Option<MoonPhaseEntry>,
+}
+
+impl Lunation {
+    /// `true` when all four phases are present
+    pub fn is_complete(&self) -> bool {
+        self.new_moon.is_some()
+            && self.first_quarter.is_some()
+            && self.full_moon.is_some()
+            && self.last_quarter.is_some()
+    }
+}
+
+/// A phase with the closest entries of another phase on either side, see
+/// [`MoonPhasesResponse::neighbors`]
+#[derive(Debug, Clone)]
+pub struct PhaseNeighbors {
+    pub previous: MoonPhaseEntry,
+    pub target: MoonPhaseEntry,
+    pub next: MoonPhaseEntry,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MoonPhaseEntry {
+    pub phase: MoonPhase,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    day: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    month: u8,
+    #[serde(deserialize_with = "string_or_number")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(schema_with = "schema::string_or_integer")
+    )]
+    year: u16,
+    #[serde(deserialize_with = "deser_time")]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "schema::time"))]
+    time: Time,
+}
+
+impl std::fmt::Debug for MoonPhaseEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MoonPhaseEntry")
+            .field("phase", &self.phase)
+            .field(
+                "date",
+                &format_args!("{:04}-{:02}-{:02}", self.year, self.month, self.day),
+            )
+            .field("time", &format_args!("{}", self.time))
+            .finish()
+    }
+}
+
+impl MoonPhaseEntry {
+    fn sort_key(&self) -> (u16, u8, u8, u8, u8) {
+        (
+            self.year,
+            self.month,
+            self.day,
+            self.time.hour,
+            self.time.minute,
+        )
+    }
+
+    pub fn when(&self) -> Result<PrimitiveDateTime> {
+        let month = time::Month::try_from(self.month).map_err(|e| {
+            anyhow::anyhow!("Invalid month in date: {e}")
+        })?;
+        let dt = Date::from_calendar_date(self.year as _, month, self.day).map_err(|e| {
+            anyhow::anyhow!("invalid date: {e}")
+        })?;
+        let t = self.time.to_time()?;
+        Ok(PrimitiveDateTime::new(dt, t))
+    }
+
+    /// The instant of this phase in `offset`, this assumes the entry was
+    /// reported in UTC which is the case for phase requests without a `tz`,
+    /// use [`MoonPhasesResponse::instant`] for any response. The date rolls
+    /// over as needed.
+    pub fn when_in_offset(&self, offset: time::UtcOffset) -> Result<OffsetDateTime> {
+        Ok(self.when()?.assume_utc().to_offset(offset))
+    }
+}
+
+/// The field names and values the USNO API uses on the wire, for mirroring
+/// them in your own `#[derive(Deserialize)]` types without depending on this
+/// crate's model
+///
+/// The crate's types serialize with their Rust field names and accept these
+/// as aliases when deserializing, so both forms round trip.
+pub mod wire {
+    /// The top level of a one day response
+    pub const PROPERTIES: &str = "properties";
+    /// Inside [`PROPERTIES`], holds the fields below
+    pub const DATA: &str = "data";
+    pub const GEOMETRY: &str = "geometry";
+    pub const API_VERSION: &str = "apiversion";
+
+    pub const CLOSEST_PHASE: &str = "closestphase";
+    pub const CURRENT_PHASE: &str = "curphase";
+    /// A percentage string like `"8%"`
+    pub const FRACTION_ILLUMINATED: &str = "fracillum";
+    pub const MOON_DATA: &str = "moondata";
+    pub const SUN_DATA: &str = "sundata";
+    pub const DAY_OF_WEEK: &str = "day_of_week";
+    pub const IS_DST: &str = "isdst";
+    pub const LABEL: &str = "label";
+    pub const TZ: &str = "tz";
+
+    /// Dates are split into these, the values may be strings or numbers
+    pub const YEAR: &str = "year";
+    pub const MONTH: &str = "month";
+    pub const DAY: &str = "day";
+    /// An `"HH:MM"` string
+    pub const TIME: &str = "time";
+
+    /// The event name in [`MOON_DATA`] and [`SUN_DATA`] entries
+    pub const PHENOMENON: &str = "phen";
+    pub const PHASE: &str = "phase";
+
+    /// The number of entries in a phases response
+    pub const NUM_PHASES: &str = "numphases";
+    pub const PHASE_DATA: &str = "phasedata";
+
+    pub const NEW_MOON: &str = "New Moon";
+    pub const WAXING_CRESCENT: &str = "Waxing Crescent";
+    pub const FIRST_QUARTER: &str = "First Quarter";
+    pub const WAXING_GIBBOUS: &str = "Waxing Gibbous";
+    pub const FULL_MOON: &str = "Full Moon";
+    pub const WANING_GIBBOUS: &str = "Waning Gibbous";
+    pub const LAST_QUARTER: &str = "Last Quarter";
+    pub const WANING_CRESCENT: &str = "Waning Crescent";
+
+    pub const RISE: &str = "Rise";
+    pub const UPPER_TRANSIT: &str = "Upper Transit";
+    pub const SET: &str = "Set";
+    pub const BEGIN_CIVIL_TWILIGHT: &str = "Begin Civil Twilight";
+    pub const END_CIVIL_TWILIGHT: &str = "End Civil Twilight";
+}
+
+/// JSON Schemas for the response and argument types, enabled with the
+/// `schemars` feature
+///
+/// The schemas describe the form these types serialize to, the USNO field
+/// names that are accepted as aliases when deserializing aren't included.
+/// Fields the API sends as strings, like numeric dates and `"HH:MM"` times,
+/// accept either representation.
+#[cfg(feature = "schemars")]
+pub mod schema {
+    use schemars::{
+        gen::SchemaGenerator,
+        schema::{RootSchema, Schema},
+        schema_for,
+    };
+
+    /// The schema for [`OneDay`](super::OneDay)
+    pub fn one_day() -> RootSchema {
+        schema_for!(super::OneDay)
+    }
+
+    /// The schema for [`MoonPhasesResponse`](super::MoonPhasesResponse)
+    pub fn phases() -> RootSchema {
+        schema_for!(super::MoonPhasesResponse)
+    }
+
+    /// The schema for [`OneDayArgs`](super::OneDayArgs)
+    pub fn one_day_args() -> RootSchema {
+        schema_for!(super::OneDayArgs)
+    }
+
+    /// The schema for [`PhaseArgs`](super::PhaseArgs)
+    pub fn phase_args() -> RootSchema {
+        schema_for!(super::PhaseArgs)
+    }
+
+    pub(crate) fn from_json(value: serde_json::Value) -> Schema {
+        serde_json::from_value(value).expect("schema literals are valid")
+    }
+
+    /// A string that is usually one of `known`, unrecognized values are
+    /// still accepted unless the `strict` feature is enabled
+    pub(crate) fn known_names(known: &[serde_json::Value]) -> Schema {
+        if cfg!(feature = "strict") {
+            from_json(serde_json::json!({ "type": "string", "enum": known }))
+        } else {
+            from_json(serde_json::json!({ "type": "string", "examples": known }))
+        }
+    }
+
+    pub(crate) fn string_or_integer(_: &mut SchemaGenerator) -> Schema {
+        from_json(serde_json::json!({
+            "anyOf": [
+                { "type": "integer", "minimum": 0 },
+                { "type": "string", "pattern": "^\\d+$" },
+            ],
+        }))
+    }
+
+    pub(crate) fn string_or_number(_: &mut SchemaGenerator) -> Schema {
+        from_json(serde_json::json!({
+            "anyOf": [
+                { "type": "number" },
+                { "type": "string", "pattern": "^\\s*-?\\d+([.,]\\d+)?\\s*$" },
+            ],
+        }))
+    }
+
+    pub(crate) fn time(_: &mut SchemaGenerator) -> Schema {
+        from_json(serde_json::json!({
+            "anyOf": [
+                { "type": "string", "pattern": "^\\d{1,2}:\\d{2}(:\\d{2})?$" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "hour": { "type": "integer", "minimum": 0, "maximum": 23 },
+                        "minute": { "type": "integer", "minimum": 0, "maximum": 59 },
+                        "second": { "type": "integer", "minimum": 0, "maximum": 59 },
+                    },
+                    "required": ["hour", "minute"],
+                },
+            ],
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn one_day_args() {
+        insta::assert_json_snapshot!(OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .build()
+            .unwrap())
+    }
+
+    #[test]
+    fn one_day_simd_json() {
+        let mut bytes = include_bytes!("fixtures/one_day.json").to_vec();
+        let one_day: OneDay = simd_json::serde::from_slice(&mut bytes).unwrap();
+        let data = one_day.properties.data;
+        assert_eq!(data.percent_illuminated, Illumination(8));
+        assert_eq!(data.moon_data.len(), 3);
+        assert_eq!(data.sun_data.len(), 5);
+        assert!(matches!(data.current_phase, MoonPhase::WaningCrescent));
+        assert!(matches!(data.closest_phase.phase, MoonPhase::New));
+    }
+
+    #[test]
+    fn moon_visibility_score() {
+        let mut day: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_transit_altitude.json")).unwrap();
+        let data = &mut day.properties.data;
+        assert_eq!(data.moon_transit_altitude(), None);
+        assert_eq!(data.moon_visibility_score(), Some(0.08));
+        data.percent_illuminated.0 = 100;
+        data.moon_data[1].altitude = Some(90.0);
+        assert_eq!(data.moon_visibility_score(), Some(1.0));
+        data.moon_data[1].altitude = Some(30.0);
+        let low = data.moon_visibility_score().unwrap();
+        assert!((low - 0.5f32.sqrt()).abs() < 1e-6, "{low}");
+        data.moon_data[1].altitude = Some(-12.0);
+        assert_eq!(data.moon_visibility_score(), Some(0.0));
+        data.moon_data.clear();
+        assert_eq!(data.moon_visibility_score(), None);
+    }
+
+    #[test]
+    fn same_data() {
+        let day: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let mut fetched = day.clone();
+        fetched.fetched_at = Some(OffsetDateTime::UNIX_EPOCH);
+        fetched.from_cache = true;
+        fetched.properties.data.label = Some("home".into());
+        assert!(day.same_data(&fetched));
+        let bare: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_bare.json")).unwrap();
+        assert!(day.same_data(&bare));
+        fetched.properties.data.percent_illuminated.0 = 9;
+        assert!(!day.same_data(&fetched));
+        let next: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_next.json")).unwrap();
+        assert!(!day.same_data(&next));
+    }
+
+    #[test]
+    fn sun_transit_altitude() {
+        let with: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_transit_altitude.json")).unwrap();
+        assert_eq!(with.properties.data.sun_transit_altitude(), Some(59.4));
+        let without: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        assert_eq!(without.properties.data.sun_transit_altitude(), None);
+        assert!(without.fetched_at().is_none());
+        assert!(without.args().is_none());
+    }
+
+    #[tokio::test]
+    async fn one_day_stream() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(10)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let start = Date::from_calendar_date(2025, time::Month::April, 28).unwrap();
+        let results: Vec<_> = client
+            .one_day_stream(start, 10, Coords::new(43.9033, -91.6401), -5.0)
+            .collect()
+            .await;
+        assert_eq!(results.len(), 10);
+        assert!(results
+            .iter()
+            .all(|r| r.as_ref().is_ok_and(|day| day.fetched_at().is_some())));
+        let dates: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|r| {
+                r.url
+                    .query_pairs()
+                    .find(|(k, _)| k == "date")
+                    .map(|(_, v)| v.into_owned())
+            })
+            .collect();
+        // a few requests are in flight at once so they may arrive out of order
+        assert_eq!(dates.len(), 10);
+        assert!(dates.contains(&"2025-04-28".to_string()));
+        assert!(dates.contains(&"2025-05-07".to_string()));
+        let day = results[9].as_ref().unwrap();
+        let args = day.args().unwrap();
+        assert_eq!(args.date, "2025-05-07");
+        assert_eq!(args.coords, "43.9033,-91.6401");
+        assert_eq!(args.tz, -5.0);
+        let serialized = serde_json::to_string(day).unwrap();
+        assert!(!serialized.contains("args"));
+        assert!(serde_json::from_str::<OneDay>(&serialized)
+            .unwrap()
+            .args()
+            .is_none());
+    }
+
+    #[test]
+    fn coords_approx_tz() {
+        assert_eq!(Coords::new(43.9033, -91.6401).approx_tz(), -6.0);
+        assert_eq!(Coords::new(0.0, 0.0).approx_tz(), 0.0);
+        assert_eq!(Coords::new(0.0, 7.4).approx_tz(), 0.0);
+        assert_eq!(Coords::new(0.0, 7.6).approx_tz(), 1.0);
+        assert_eq!(Coords::new(-33.8688, 151.2093).approx_tz(), 10.0);
+    }
+
+    #[test]
+    fn coords_distance_to() {
+        let london = Coords::new(51.5074, -0.1278);
+        let paris = Coords::new(48.8566, 2.3522);
+        let km = london.distance_to(&paris);
+        assert!((km - 343.5).abs() < 2.0, "{km}");
+        assert_eq!(london.distance_to(&london), 0.0);
+    }
+
+    #[test]
+    fn phases_to_csv() {
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_year.json")).unwrap();
+        let csv = phases.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("date,time,phase"));
+        assert_eq!(lines.next(), Some("2025-01-06,23:57,First Quarter"));
+        assert_eq!(csv.lines().count(), phases.phases.len() + 1);
+    }
+
+    #[test]
+    fn events_to_csv() {
+        let one_day: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let csv = one_day.properties.data.events_to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("body,phenomenon,time"));
+        assert_eq!(lines.next(), Some("sun,Begin Civil Twilight,05:35"));
+        assert_eq!(csv.lines().count(), 9);
+        assert_eq!(csv.lines().filter(|l| l.starts_with("moon,")).count(), 3);
+    }
+
+    #[test]
+    fn csv_escaping() {
+        assert_eq!(csv_field("Full Moon"), "Full Moon");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn moon_age() {
+        // The moon was ~26.9 days old at the start of 2025-04-25 in UTC-5,
+        // the new moon following on 2025-04-27 19:31 UTC
+        let one_day: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let age = one_day.properties.data.moon_age().unwrap();
+        assert!((age - 26.9).abs() < 1.0, "{age}");
+    }
+
+    fn query_string(args: &impl Serialize) -> String {
+        reqwest::Client::new()
+            .get("http://localhost/")
+            .query(args)
+            .build()
+            .unwrap()
+            .url()
+            .query()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[test]
+    fn phases_query_omits_coords() {
+        for args in [
+            PhaseArgs::year(2025),
+            PhaseArgs::build_by_date()
+                .year(2025)
+                .month(4)
+                .day(25)
+                .count(8)
+                .build()
+                .unwrap(),
+        ] {
+            let query = query_string(&args);
+            assert!(!query.contains("coords="), "{query}");
+            assert!(query.split('&').all(|pair| !pair.ends_with('=')), "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn one_day_request() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("date", "2025-04-25"))
+            .and(matchers::query_param("coords", "43.9033,-91.6401"))
+            .and(matchers::query_param("tz", "-5.0"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let one_day = client
+            .one_day_request()
+            .date(Date::from_calendar_date(2025, time::Month::April, 25).unwrap())
+            .coords(Coords::new(43.9033, -91.6401))
+            .tz(-5.0)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(one_day.properties.data.percent_illuminated, Illumination(8));
+        let awaited = client
+            .one_day_request()
+            .date(Date::from_calendar_date(2025, time::Month::April, 25).unwrap())
+            .coords(Coords::new(43.9033, -91.6401))
+            .tz(-5.0)
+            .await
+            .unwrap();
+        assert_eq!(awaited.properties.data.percent_illuminated, Illumination(8));
+    }
+
+    #[test]
+    fn symbol_ascii() {
+        let symbols: Vec<_> = MoonPhase::all()
+            .iter()
+            .map(MoonPhase::symbol_ascii)
+            .collect();
+        assert_eq!(symbols, ["( )", "(|", "(D", "(0", "(O)", "0)", "C)", "|)"]);
+        assert_eq!(MoonPhase::Unknown("Blue Moon".into()).symbol_ascii(), "(?)");
+        assert!(symbols.iter().all(|symbol| symbol.is_ascii()));
+    }
+
+    #[test]
+    fn age_days() {
+        let ages: Vec<_> = [
+            MoonPhase::New,
+            MoonPhase::FirstQuarter,
+            MoonPhase::Full,
+            MoonPhase::LastQuarter,
+        ]
+        .iter()
+        .map(|phase| phase.typical_age_days().unwrap())
+        .collect();
+        for (age, expected) in ages.iter().zip([0.0, 7.38, 14.77, 22.15]) {
+            assert!((age - expected).abs() < 0.01, "{age} != {expected}");
+        }
+        assert_eq!(
+            MoonPhase::Unknown("Blue Moon".into()).typical_age_days(),
+            None
+        );
+        for phase in MoonPhase::all() {
+            let age = phase.typical_age_days().unwrap();
+            for jitter in [-1.5, 0.0, 1.5] {
+                assert_eq!(
+                    MoonPhase::from_age_days(age + jitter),
+                    phase,
+                    "{age} {jitter}"
+                );
+            }
+        }
+        assert_eq!(MoonPhase::from_age_days(29.3), MoonPhase::New);
+        assert_eq!(MoonPhase::from_age_days(-7.4), MoonPhase::LastQuarter);
+        assert_eq!(MoonPhase::from_age_days(44.3), MoonPhase::Full);
+    }
+
+    #[test]
+    fn phase_index_round_trip() {
+        for index in 0..8 {
+            let phase = MoonPhase::from_index(index).unwrap();
+            assert_eq!(phase.as_index(), Some(index));
+        }
+        assert!(MoonPhase::from_index(8).is_none());
+
+        #[derive(Serialize, Deserialize)]
+        struct Compact {
+            #[serde(with = "phase_index")]
+            phase: MoonPhase,
+        }
+        let json = serde_json::to_string(&Compact {
+            phase: MoonPhase::LastQuarter,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"phase":6}"#);
+        let back: Compact = serde_json::from_str(&json).unwrap();
+        assert!(matches!(back.phase, MoonPhase::LastQuarter));
+        assert!(serde_json::from_str::<Compact>(r#"{"phase":9}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn default_headers() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::header("accept-language", "de"))
+            .and(matchers::header("x-correlation-id", "abc-123"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("fixtures/phases_year.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_header("Accept-Language", "de")
+            .unwrap()
+            .with_header("X-Correlation-Id", "abc-123")
+            .unwrap();
+        client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(Client::default().with_header("bad header", "x").is_err());
+        assert!(Client::default().with_header("X-Ok", "bad\nvalue").is_err());
+    }
+
+    #[test]
+    fn wire_names() {
+        let one_day: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        assert!(one_day[wire::GEOMETRY].is_object());
+        assert!(one_day[wire::API_VERSION].is_string());
+        let data = &one_day[wire::PROPERTIES][wire::DATA];
+        for field in [
+            wire::CLOSEST_PHASE,
+            wire::CURRENT_PHASE,
+            wire::FRACTION_ILLUMINATED,
+            wire::MOON_DATA,
+            wire::SUN_DATA,
+            wire::DAY_OF_WEEK,
+            wire::IS_DST,
+            wire::LABEL,
+            wire::TZ,
+            wire::YEAR,
+            wire::MONTH,
+            wire::DAY,
+        ] {
+            assert!(data.get(field).is_some(), "{field}");
+        }
+        assert_eq!(data[wire::CURRENT_PHASE], wire::WANING_CRESCENT);
+        assert_eq!(data[wire::CLOSEST_PHASE][wire::PHASE], wire::NEW_MOON);
+        assert!(data[wire::CLOSEST_PHASE][wire::TIME].is_string());
+        let sun: Vec<_> = data[wire::SUN_DATA]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event[wire::PHENOMENON].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            sun,
+            [
+                wire::BEGIN_CIVIL_TWILIGHT,
+                wire::RISE,
+                wire::UPPER_TRANSIT,
+                wire::SET,
+                wire::END_CIVIL_TWILIGHT
+            ]
+        );
+        let phases: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/phases_year.json")).unwrap();
+        assert!(phases[wire::NUM_PHASES].is_number());
+        let names: std::collections::HashSet<_> = phases[wire::PHASE_DATA]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry[wire::PHASE].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                wire::NEW_MOON,
+                wire::FIRST_QUARTER,
+                wire::FULL_MOON,
+                wire::LAST_QUARTER
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn self_check() {
+        let one_day: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let data = one_day.properties.data;
+        data.self_check().unwrap();
+        let mut bad = data.clone();
+        bad.percent_illuminated = Illumination(101);
+        assert!(bad.self_check().is_err());
+        let mut bad = data.clone();
+        bad.percent_illuminated = Illumination(90);
+        assert!(bad.self_check().is_err());
+        let mut bad = data.clone();
+        bad.closest_phase.month = 6;
+        assert!(bad.self_check().is_err());
+        let mut unknown = data.clone();
+        unknown.current_phase = MoonPhase::Unknown("Blue Moon".into());
+        unknown.percent_illuminated = Illumination(90);
+        unknown.self_check().unwrap();
+    }
+
+    #[test]
+    fn closest_phase_is_future() {
+        let one_day: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let mut data = one_day.properties.data;
+        assert!(data.closest_phase_is_future().unwrap());
+        data.closest_phase.day = 25;
+        assert!(data.closest_phase_is_future().unwrap());
+        data.closest_phase.day = 24;
+        assert!(!data.closest_phase_is_future().unwrap());
+    }
+
+    #[cfg(feature = "geocode")]
+    #[tokio::test]
+    async fn one_day_for_place() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        struct Fixed;
+        impl Geocoder for Fixed {
+            async fn geocode(&self, place: &str) -> Result<Coords> {
+                if place == "La Crosse, WI" {
+                    Ok(Coords::new(43.9033, -91.6401))
+                } else {
+                    anyhow::bail!("unknown place")
+                }
+            }
+        }
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("coords", "43.9033,-91.6401"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let date = Date::from_calendar_date(2025, time::Month::April, 25).unwrap();
+        client
+            .one_day_for_place(&Fixed, "La Crosse, WI", date, -5.0)
+            .await
+            .unwrap();
+        assert!(client
+            .one_day_for_place(&Fixed, "Atlantis", date, -5.0)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn group_by_month() {
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        let groups = phases.group_by_month();
+        assert_eq!(
+            groups.keys().copied().collect::<Vec<_>>(),
+            [(2025, 12), (2026, 1)]
+        );
+        let dec: Vec<u8> = groups[&(2025, 12)].iter().map(|e| e.day).collect();
+        assert_eq!(dec, [4, 11, 20, 27]);
+        let jan: Vec<u8> = groups[&(2026, 1)].iter().map(|e| e.day).collect();
+        assert_eq!(jan, [3, 10, 18, 26]);
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        let one_day = serde_json::to_value(schema::one_day()).unwrap();
+        let data = &one_day["definitions"]["OneDayData"]["properties"];
+        assert!(data["closest_phase"].is_object());
+        assert!(data["year"]["anyOf"].is_array());
+        assert!(data.get("from_cache").is_none());
+        let event = &one_day["definitions"]["CelestialEvent"]["properties"];
+        assert_eq!(event["time"]["anyOf"][0]["type"], "string");
+        let pattern =
+            regex::Regex::new(event["time"]["anyOf"][0]["pattern"].as_str().unwrap()).unwrap();
+        for time in ["12:34", "12:34:56"] {
+            assert!(pattern.is_match(time), "{time}");
+            let event = serde_json::json!({ "phen": "Rise", "time": time });
+            assert!(serde_json::from_value::<CelestialEvent>(event).is_ok());
+        }
+        assert!(!pattern.is_match("12:34:5"));
+        assert_eq!(
+            event["time"]["anyOf"][1]["properties"]["second"]["type"],
+            "integer"
+        );
+        let phases = serde_json::to_value(schema::phases()).unwrap();
+        let known = if cfg!(feature = "strict") {
+            "enum"
+        } else {
+            "examples"
+        };
+        assert_eq!(
+            phases["definitions"]["MoonPhase"][known]
+                .as_array()
+                .unwrap()
+                .len(),
+            8
+        );
+        let args = serde_json::to_value(schema::phase_args()).unwrap();
+        assert_eq!(args["anyOf"].as_array().unwrap().len(), 2);
+        assert!(
+            serde_json::to_value(schema::one_day_args()).unwrap()["properties"]["coords"]
+                .is_object()
+        );
+    }
+
+    #[tokio::test]
+    async fn metrics() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        #[derive(Default, Clone)]
+        struct Recorder(std::sync::Arc<Mutex<Vec<String>>>);
+        impl Metrics for Recorder {
+            fn on_request(&self, endpoint: &str) {
+                self.0.lock().unwrap().push(format!("request {endpoint}"));
+            }
+            fn on_success(&self, endpoint: &str, _: std::time::Duration) {
+                self.0.lock().unwrap().push(format!("success {endpoint}"));
+            }
+            fn on_error(&self, endpoint: &str, kind: RequestErrorKind) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("error {endpoint} {kind:?}"));
+            }
+        }
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("date", "2025-04-25"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("date", "2026-04-25"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"properties\": {}}"))
+            .mount(&server)
+            .await;
+        let recorder = Recorder::default();
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_metrics(recorder.clone());
+        client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        client.one_day(&args).await.unwrap_err();
+        let next_year = OneDayArgs::builder()
+            .year(2026)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        client.one_day(&next_year).await.unwrap_err();
+        client.one_day_bytes(&next_year).await.unwrap();
+        let unreachable = Client::with_base_url("http://127.0.0.1:1")
+            .unwrap()
+            .with_metrics(recorder.clone());
+        unreachable.one_day(&args).await.unwrap_err();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "request /api/moon/phases/year",
+                "success /api/moon/phases/year",
+                "request /api/rstt/oneday",
+                "error /api/rstt/oneday Status(503)",
+                "request /api/rstt/oneday",
+                "error /api/rstt/oneday Decode",
+                "request /api/rstt/oneday",
+                "success /api/rstt/oneday",
+                "request /api/rstt/oneday",
+                "error /api/rstt/oneday Send",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn single_flight() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::query_param("year", "2025"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(std::time::Duration::from_millis(200))
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::query_param("year", "2024"))
+            .respond_with(
+                ResponseTemplate::new(500).set_delay(std::time::Duration::from_millis(200)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_single_flight();
+        let args = PhaseArgs::year(2025);
+        let results = futures::future::join_all((0..10).map(|_| client.phases(&args))).await;
+        assert!(results
+            .iter()
+            .all(|result| result.as_ref().unwrap().phases.len() == 49));
+        let args = PhaseArgs::year(2024);
+        let results = futures::future::join_all((0..5).map(|_| client.phases(&args))).await;
+        let errors: Vec<_> = results.iter().map(|r| r.as_ref().unwrap_err()).collect();
+        assert!(errors
+            .iter()
+            .all(|e| e.to_string().contains("invalid status in response")));
+        let is_reqwest = |e: &&&anyhow::Error| {
+            e.chain()
+                .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some())
+        };
+        // only the sender keeps the source, the others get the same message
+        let sender: Vec<_> = errors.iter().filter(is_reqwest).collect();
+        assert_eq!(sender.len(), 1);
+        assert_eq!(
+            sender[0]
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<reqwest::Error>())
+                .unwrap()
+                .status(),
+            Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        );
+        assert!(errors
+            .iter()
+            .all(|e| format!("{e:#}") == format!("{:#}", sender[0])));
+        assert!(client
+            .in_flight
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn fractional_tz_correction() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let hm = |hour, minute| time::Time::from_hms(hour, minute, 0).unwrap();
+        let server = MockServer::start().await;
+        // a server that ignored the half hour of `tz=-5.5` and echoed it
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                include_str!("./fixtures/one_day.json").replace(r#""tz": -5.0"#, r#""tz": -5.5"#),
+            ))
+            .mount(&server)
+            .await;
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.5)
+            .build()
+            .unwrap();
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let plain = client.one_day(&args).await.unwrap().properties.data;
+        assert_eq!(plain.sun().rise(), Some(hm(6, 7)));
+        let client = client.with_fractional_tz_correction();
+        let data = client.one_day(&args).await.unwrap().properties.data;
+        assert_eq!(data.tz, -5.5);
+        assert_eq!(data.sun().rise(), Some(hm(5, 37)));
+        assert_eq!(data.moon().rise(), Some(hm(4, 43)));
+
+        // a server that echoed the whole hours it used
+        let mut data: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let instant = |data: &OneDayData| {
+            data.closest_phase
+                .when()
+                .unwrap()
+                .assume_offset(data.utc_offset().unwrap())
+        };
+        let phase_instant = instant(&data.properties.data);
+        data.properties.data.apply_fractional_tz(-4.5).unwrap();
+        assert_eq!(data.properties.data.tz, -4.5);
+        assert_eq!(data.properties.data.sun().rise(), Some(hm(6, 37)));
+        assert_eq!(
+            data.properties.data.closest_phase.when().unwrap().time(),
+            hm(15, 1)
+        );
+        // the phase happens at the same instant, it's only read at -4:30
+        assert_eq!(instant(&data.properties.data), phase_instant);
+        let before = data.properties.data.events_to_csv();
+        data.properties.data.apply_fractional_tz(-4.0).unwrap();
+        assert_eq!(data.properties.data.events_to_csv(), before);
+        data.properties.data.tz = 0.0;
+        data.properties.data.sun_data[0].time = Time {
+            hour: 23,
+            minute: 50,
+            second: 0,
+        };
+        data.properties.data.closest_phase.time = Time {
+            hour: 23,
+            minute: 50,
+            second: 0,
+        };
+        data.properties.data.apply_fractional_tz(0.5).unwrap();
+        assert_eq!(data.properties.data.sun_data[0].when().unwrap(), hm(0, 20));
+        assert_eq!(
+            data.properties.data.closest_phase.when().unwrap(),
+            Date::from_calendar_date(2025, time::Month::April, 28)
+                .unwrap()
+                .with_time(hm(0, 20))
+        );
+
+        let before = data.properties.data.events_to_csv();
+        assert!(data.properties.data.apply_fractional_tz(1000.0).is_err());
+        data.properties.data.tz = 1e9;
+        assert!(data.properties.data.apply_fractional_tz(0.5).is_err());
+        assert_eq!(data.properties.data.events_to_csv(), before);
+    }
+
+    #[tokio::test]
+    async fn phase_neighbors() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("./fixtures/phases_year.json")).unwrap();
+        let date = |month, day| Date::from_calendar_date(2025, month, day).unwrap();
+        let dates = |neighbors: &PhaseNeighbors| {
+            [&neighbors.previous, &neighbors.target, &neighbors.next]
+                .map(|entry| entry.when().unwrap().date())
+        };
+        let neighbors = phases
+            .neighbors(
+                date(time::Month::January, 14).midnight(),
+                &MoonPhase::Full,
+                &MoonPhase::New,
+            )
+            .unwrap();
+        assert_eq!(neighbors.target.phase, MoonPhase::Full);
+        assert_eq!(
+            dates(&neighbors),
+            [
+                date(time::Month::January, 29),
+                date(time::Month::February, 12),
+                date(time::Month::February, 28)
+            ]
+        );
+        // no new moon before the first full moon of the fixture
+        assert!(phases
+            .neighbors(
+                date(time::Month::January, 1).midnight(),
+                &MoonPhase::Full,
+                &MoonPhase::New
+            )
+            .is_none());
+        assert!(phases
+            .neighbors(
+                date(time::Month::January, 1).midnight(),
+                &MoonPhase::WaxingGibbous,
+                &MoonPhase::New
+            )
+            .is_none());
+
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/date"))
+            .and(matchers::query_param("date", "2025-01-12"))
+            .and(matchers::query_param("nump", "16"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let neighbors = client
+            .phase_neighbors(
+                date(time::Month::February, 13),
+                MoonPhase::New,
+                MoonPhase::Full,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            dates(&neighbors),
+            [
+                date(time::Month::February, 12),
+                date(time::Month::February, 28),
+                date(time::Month::March, 14)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn correlation_id() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::header(CORRELATION_ID_HEADER, "trace-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("./fixtures/one_day.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::header(CORRELATION_ID_HEADER, "trace-2"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        client
+            .one_day_with_correlation_id(&args, "trace-1")
+            .await
+            .unwrap();
+        let err = client
+            .one_day_with_correlation_id(&args, "trace-2")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "request with correlation id trace-2 failed"
+        );
+        assert!(format!("{err:#}").contains("invalid status in response"));
+        let err = client
+            .one_day_with_correlation_id(&args, "bad\nid")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("bad\nid"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn per_call_headers() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::header("X-Variant", "b"))
+            .and(matchers::header("X-Flag", "on"))
+            .and(matchers::header("Accept-Language", "en"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("./fixtures/one_day.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::header("X-Variant", "a"))
+            .and(matchers::header("X-Flag", "on"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_header("X-Variant", "a")
+            .unwrap()
+            .with_header("Accept-Language", "en")
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Variant", HeaderValue::from_static("b"));
+        headers.insert("X-Flag", HeaderValue::from_static("on"));
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        client.one_day_with_headers(&args, &headers).await.unwrap();
+        headers.remove("X-Variant");
+        client
+            .phases_with_headers(&PhaseArgs::year(2025), &headers)
+            .await
+            .unwrap();
+        // per-call headers don't leak into later calls
+        client.phases(&PhaseArgs::year(2025)).await.unwrap_err();
+        client
+            .phases_with_headers(&PhaseArgs::year(2025), &headers)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn one_day_grid() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("coords", "45.0000,-90.0000"))
+            .respond_with(ResponseTemplate::new(500))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(std::time::Duration::from_millis(20))
+                    .set_body_string(include_str!("./fixtures/one_day.json")),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let date = Date::from_calendar_date(2025, time::Month::April, 25).unwrap();
+        let cells: Vec<_> = (0..3)
+            .flat_map(|lat| {
+                (0..3).map(move |long| (date, Coords::new(43.0 + lat as f32, -92.0 + long as f32)))
+            })
+            .collect();
+        let results = client.one_day_grid(&cells, -5.0, 3).await;
+        assert_eq!(results.len(), 9);
+        for ((_, coords), result) in cells.iter().zip(&results) {
+            if (coords.lat, coords.long) == (45.0, -90.0) {
+                assert!(result.is_err());
+                continue;
+            }
+            let args = result.as_ref().unwrap().args().unwrap();
+            assert_eq!(
+                args.coords,
+                format!("{:.04},{:.04}", coords.lat, coords.long)
+            );
+        }
+        assert!(client.one_day_grid(&[], -5.0, 0).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn redirect_policy() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(301).insert_header("Location", "/v2/moon/phases/year"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/v2/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/api/rstt/oneday"))
+            .mount(&server)
+            .await;
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        let client = Client::with_base_url(server.uri()).unwrap();
+        client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        let err = client.one_day(&args).await.unwrap_err();
+        assert!(format!("{err:#}").contains("too many redirects"), "{err:#}");
+
+        let client = client
+            .with_redirect_policy(RedirectPolicy::Limit(1))
+            .unwrap();
+        client.phases(&PhaseArgs::year(2025)).await.unwrap();
+
+        let client = client.with_redirect_policy(RedirectPolicy::Never).unwrap();
+        let err = client.phases(&PhaseArgs::year(2025)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "redirected to \"/v2/moon/phases/year\" (301 Moved Permanently) but redirects aren't followed"
+        );
+    }
+
+    #[tokio::test]
+    async fn html_response() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        let page = "<!DOCTYPE html>\n<html>\n  <body>\n    <h1>Down for maintenance</h1>\n  </body>\n</html>";
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(format!("\n{page}"), "application/json"),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let err = client.phases(&PhaseArgs::year(2025)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "non-JSON response (service unavailable?): \"<!DOCTYPE html> <html> <body> \
+             <h1>Down for maintenance</h1> </body> </html>\""
+        );
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .lat(43.9)
+            .long(-91.6)
+            .tz(-5.0)
+            .build()
+            .unwrap();
+        let err = client.one_day(&args).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("non-JSON response (service unavailable?)"),
+            "{err:#}"
+        );
+        assert_eq!(snippet("é".repeat(150).as_bytes()).chars().count(), 101);
+    }
+
+    #[tokio::test]
+    async fn warm_up() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::method("HEAD"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Client::with_base_url(server.uri())
+            .unwrap()
+            .warm_up()
+            .await
+            .unwrap();
+        let err = Client::with_base_url("http://127.0.0.1:1")
+            .unwrap()
+            .warm_up()
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "failed to warm up connection");
+    }
+
+    #[tokio::test]
+    async fn http_version() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+        for version in [
+            HttpVersion::Negotiate,
+            HttpVersion::Http1Only,
+            HttpVersion::Http2PriorKnowledge,
+        ] {
+            let client = Client::with_base_url(server.uri())
+                .unwrap()
+                .with_http_version(version)
+                .unwrap();
+            client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn compressed_response() {
+        use std::io::Write;
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder
+            .write_all(include_bytes!("./fixtures/phases_year.json"))
+            .unwrap();
+        let body = encoder.finish().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::header_exists("Accept-Encoding"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .set_body_bytes(body),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let phases = client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert_eq!(phases.count as usize, phases.phases.len());
+        let client = client.with_http_version(HttpVersion::Http1Only).unwrap();
+        let phases = client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert_eq!(phases.count as usize, phases.phases.len());
+    }
+
+    #[test]
+    fn estimated_illumination() {
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("./fixtures/phases_year.json")).unwrap();
+        let at = |month, day, hour, minute| {
+            Date::from_calendar_date(2025, month, day)
+                .unwrap()
+                .with_hms(hour, minute, 0)
+                .unwrap()
+        };
+        let full = at(time::Month::January, 13, 22, 28);
+        let new = at(time::Month::January, 29, 12, 36);
+        assert_eq!(phases.estimated_illumination(full), Some(100));
+        assert_eq!(phases.estimated_illumination(new), Some(0));
+        assert_eq!(
+            phases.estimated_illumination(full + (new - full) / 2),
+            Some(50)
+        );
+        let last_quarter = phases
+            .estimated_illumination(at(time::Month::January, 21, 20, 31))
+            .unwrap();
+        assert!((45..=55).contains(&last_quarter), "{last_quarter}");
+        let waxing = phases
+            .estimated_illumination(at(time::Month::February, 5, 8, 3))
+            .unwrap();
+        assert!((45..=55).contains(&waxing), "{waxing}");
+        assert_eq!(
+            phases.estimated_illumination(at(time::Month::January, 6, 0, 0)),
+            None
+        );
+        assert_eq!(
+            phases.estimated_illumination(at(time::Month::December, 31, 23, 59)),
+            None
+        );
+        let gap = MoonPhasesResponse {
+            phases: phases
+                .phases
+                .iter()
+                .filter(|entry| entry.phase != MoonPhase::Full || entry.month != 2)
+                .cloned()
+                .collect(),
+            ..phases.clone()
+        };
+        assert_eq!(
+            gap.estimated_illumination(at(time::Month::February, 12, 13, 54)),
+            None
+        );
+    }
+
+    #[test]
+    fn lunations() {
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("./fixtures/phases_year.json")).unwrap();
+        let lunations = phases.lunations();
+        assert_eq!(lunations.len(), 13);
+        let first = &lunations[0];
+        assert!(first.new_moon.is_none());
+        assert_eq!(first.full_moon.as_ref().unwrap().day, 13);
+        assert!(lunations[1..12].iter().all(Lunation::is_complete));
+        let last = lunations.last().unwrap();
+        assert_eq!(last.new_moon.as_ref().unwrap().day, 20);
+        assert!(last.first_quarter.is_some());
+        assert!(last.full_moon.is_none() && last.last_quarter.is_none());
+        for lunation in &lunations[1..] {
+            let new_moon = lunation.new_moon.as_ref().unwrap().when().unwrap();
+            for entry in [
+                &lunation.first_quarter,
+                &lunation.full_moon,
+                &lunation.last_quarter,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                let age = entry.when().unwrap() - new_moon;
+                assert!(age.is_positive() && age < time::Duration::days(30));
+            }
+        }
+
+        // a missing new moon splits the cycle rather than merging two
+        let gap = MoonPhasesResponse {
+            phases: phases
+                .phases
+                .iter()
+                .filter(|entry| !(entry.phase == MoonPhase::New && entry.month == 2))
+                .cloned()
+                .collect(),
+            ..phases.clone()
+        };
+        let lunations = gap.lunations();
+        assert_eq!(lunations.len(), 13);
+        assert!(lunations[2].new_moon.is_none());
+        assert!(lunations[2].first_quarter.is_some());
+    }
+
+    #[test]
+    fn with_countdowns() {
+        let mut phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        // New Moon on 2025-12-20 at 01:44
+        let now = Date::from_calendar_date(2025, time::Month::December, 19)
+            .unwrap()
+            .with_hms(1, 44, 0)
+            .unwrap();
+        let countdowns = phases.with_countdowns(now);
+        assert_eq!(countdowns.len(), 8);
+        assert_eq!(countdowns[2].0.phase, MoonPhase::New);
+        assert_eq!(countdowns[2].1, time::Duration::DAY);
+        assert!(countdowns[..2].iter().all(|(_, until)| until.is_negative()));
+        assert!(countdowns[3..].iter().all(|(_, until)| until.is_positive()));
+        phases.phases[0].month = 13;
+        assert_eq!(phases.with_countdowns(now).len(), 7);
+    }
+
+    #[test]
+    fn principal_and_intermediate() {
+        let mut phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        assert_eq!(phases.principal_only().len(), 8);
+        assert!(phases.intermediate_only().is_empty());
+        phases.phases[0].phase = MoonPhase::WaningGibbous;
+        phases.phases[1].phase = MoonPhase::Unknown("Balsamic".into());
+        assert_eq!(phases.principal_only().len(), 6);
+        let intermediate = phases.intermediate_only();
+        assert_eq!(intermediate.len(), 1);
+        assert_eq!(intermediate[0].phase, MoonPhase::WaningGibbous);
+        for phase in MoonPhase::all() {
+            assert_ne!(phase.is_principal(), phase.is_intermediate());
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_bytes() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .build()
+            .unwrap();
+        assert_eq!(client.one_day_bytes(&args).await.unwrap(), "not json");
+        assert!(client.phases_bytes(&PhaseArgs::year(2025)).await.is_err());
+    }
+
+    #[test]
+    fn lenient_tz() {
+        let one_day = include_str!("./fixtures/one_day.json");
+        assert!(one_day.contains(r#""tz": -5.0"#));
+        for (tz, expected) in [("-5.5", -5.5), (r#""-5.5""#, -5.5), (r#""-5,5""#, -5.5)] {
+            let parsed: OneDay =
+                serde_json::from_str(&one_day.replace(r#""tz": -5.0"#, &format!(r#""tz": {tz}"#)))
+                    .unwrap();
+            assert_eq!(parsed.properties.data.tz, expected, "{tz}");
+        }
+        assert!(serde_json::from_str::<OneDay>(
+            &one_day.replace(r#""tz": -5.0"#, r#""tz": "west""#)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn illumination() {
+        for (json, expected) in [
+            (r#""8%""#, 8),
+            (r#""100%""#, 100),
+            ("0", 0),
+            ("57", 57),
+            (r#""7.3%""#, 7),
+            (r#""7,3%""#, 7),
+            ("7.6", 8),
+        ] {
+            let parsed: Illumination = serde_json::from_str(json).unwrap();
+            assert_eq!(parsed, Illumination(expected), "{json}");
+        }
+        for json in [
+            r#""abc""#,
+            r#""101%""#,
+            "101",
+            "-1",
+            r#""""#,
+            r#""7,3,1%""#,
+            r#""-0.4%""#,
+            r#""NaN%""#,
+        ] {
+            assert!(
+                serde_json::from_str::<Illumination>(json).is_err(),
+                "{json}"
+            );
+        }
+        assert_eq!(serde_json::to_string(&Illumination(8)).unwrap(), "8");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn illumination_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Illumination)).unwrap();
+        let pattern = regex::Regex::new(schema["anyOf"][1]["pattern"].as_str().unwrap()).unwrap();
+        for value in [
+            "8%", "100%", "57", " 57 ", "7.3%", "7,3%", "0.5 %", "abc", "", "7,3,1%", "-0.4%",
+            "NaN%", "7.%",
+        ] {
+            assert_eq!(
+                pattern.is_match(value),
+                serde_json::from_value::<Illumination>(serde_json::json!(value)).is_ok(),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn phases_sorted_and_deduplicated() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/date"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("fixtures/phases_unsorted.json")),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let args = PhaseArgs::build_by_date()
+            .year(2025)
+            .month(12)
+            .day(1)
+            .count(6)
+            .build()
+            .unwrap();
+        let phases = client.phases(&args).await.unwrap();
+        assert_eq!(phases.count, 5);
+        let when: Vec<_> = phases.phases.iter().map(|p| p.when().unwrap()).collect();
+        assert_eq!(when.len(), 5);
+        assert!(when.windows(2).all(|w| w[0] < w[1]), "{when:?}");
+        assert_eq!(phases.phases[0].phase, MoonPhase::Full);
+        assert_eq!(phases.phases[4].phase, MoonPhase::Full);
+    }
+
+    #[test]
+    fn month_typed() {
+        let typed = OneDayArgs::builder()
+            .year(2025)
+            .month_typed(time::Month::April)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .build()
+            .unwrap();
+        assert_eq!(typed.date, "2025-04-25");
+        let typed = PhaseArgs::build_by_date()
+            .year(2025)
+            .month_typed(time::Month::December)
+            .day(1)
+            .count(4)
+            .build()
+            .unwrap();
+        assert!(matches!(typed, PhaseArgs::ByDate { date, .. } if date == "2025-12-01"));
+    }
+
+    #[test]
+    fn by_date_through() {
+        let date = |year, month, day| Date::from_calendar_date(year, month, day).unwrap();
+        let nump = |args: PhaseArgs| match args {
+            PhaseArgs::ByDate { nump, .. } => nump,
+            PhaseArgs::Year { .. } => unreachable!(),
+        };
+        let through = |end| {
+            PhaseArgs::build_by_date()
+                .year(2025)
+                .month(1)
+                .day(1)
+                .through(end)
+                .map(|builder| builder.build().unwrap())
+        };
+        assert_eq!(
+            nump(through(date(2025, time::Month::January, 1)).unwrap()),
+            1
+        );
+        // 2025 has 49 phases from Jan 1st through Dec 31st
+        let year = nump(through(date(2025, time::Month::December, 31)).unwrap());
+        assert!((49..=51).contains(&year), "{year}");
+        let long = nump(through(date(2026, time::Month::November, 1)).unwrap());
+        assert!(long <= 99, "{long}");
+        let err = through(date(2027, time::Month::January, 1)).unwrap_err();
+        assert!(err.to_string().contains("at most 99"), "{err}");
+        assert!(through(date(2024, time::Month::December, 31)).is_err());
+        assert!(PhaseArgs::build_by_date()
+            .year(2025)
+            .month(2)
+            .day(30)
+            .through(date(2025, time::Month::April, 1))
+            .is_err());
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        let mid: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        assert!(!mid.properties.data.is_polar_day());
+        assert!(!mid.properties.data.is_polar_night());
+        let summer: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_polar_day.json")).unwrap();
+        assert!(summer.properties.data.is_polar_day());
+        assert!(!summer.properties.data.is_polar_night());
+        let winter: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_polar_night.json")).unwrap();
+        assert!(!winter.properties.data.is_polar_day());
+        assert!(winter.properties.data.is_polar_night());
+        let mut deep_winter = winter.properties.data.clone();
+        deep_winter
+            .sun_data
+            .retain(|e| matches!(e.phenomenon, Phenomenon::Apex));
+        deep_winter.sun_data[0].altitude = Some(-8.2);
+        assert!(!deep_winter.is_polar_day());
+        assert!(deep_winter.is_polar_night());
+        let unknown: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_deep_polar_night.json")).unwrap();
+        assert_eq!(unknown.properties.data.sun_transit_altitude(), None);
+        assert!(!unknown.properties.data.is_polar_day());
+        assert!(!unknown.properties.data.is_polar_night());
+    }
+
+    #[tokio::test]
+    async fn via_base_url() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let primary = MockServer::start().await;
+        let mirror = MockServer::start().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(503))
+            .expect(0)
+            .mount(&primary)
+            .await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(1)
+            .mount(&mirror)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("fixtures/phases_year.json")),
+            )
+            .expect(1)
+            .mount(&mirror)
+            .await;
+        let client = Client::with_base_url(primary.uri()).unwrap();
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .build()
+            .unwrap();
+        client.one_day_via(&mirror.uri(), &args).await.unwrap();
+        client
+            .phases_via(&mirror.uri(), &PhaseArgs::year(2025))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn phases_by_date_tz() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/date"))
+            .and(matchers::query_param("tz", "-5.0"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_by_date_tz.json")),
+            )
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/date"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_by_date.json")),
+            )
+            .mount(&server)
+            .await;
+        let args = |tz| {
+            PhaseArgs::build_by_date()
+                .year(2025)
+                .month(12)
+                .day(1)
+                .count(8)
+                .maybe_tz(tz)
+                .build()
+                .unwrap()
+        };
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let utc = client.phases(&args(None)).await.unwrap();
+        let local = client.phases(&args(Some(-5.0))).await.unwrap();
+        assert_eq!(utc.tz, None);
+        assert_eq!(local.tz, Some(-5.0));
+        let offset = time::UtcOffset::from_hms(-5, 0, 0).unwrap();
+        for (utc, local) in utc.phases.iter().zip(&local.phases) {
+            assert_eq!(utc.phase, local.phase);
+            assert_eq!(
+                utc.when_in_offset(offset).unwrap(),
+                local.when().unwrap().assume_offset(offset)
+            );
+        }
+        let new_moon = local.phases[2].when().unwrap();
+        assert_eq!((new_moon.day(), new_moon.hour()), (19, 20));
+        assert_eq!(
+            serde_json::to_value(args(Some(-5.0))).unwrap(),
+            serde_json::json!({"date": "2025-12-01", "nump": 8, "tz": -5.0})
+        );
+    }
+
+    #[test]
+    fn phases_tz_helpers() {
+        let utc: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        let local: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date_tz.json")).unwrap();
+        assert_eq!(local.tz, Some(-5.0));
+        assert_eq!(utc.utc_offset().unwrap(), time::UtcOffset::UTC);
+        for (u, l) in utc.phases.iter().zip(&local.phases) {
+            assert_eq!(utc.instant(u).unwrap(), local.instant(l).unwrap());
+            assert_eq!(
+                local.instant(l).unwrap(),
+                u.when_in_offset(local.utc_offset().unwrap()).unwrap()
+            );
+        }
+        // 2025-12-20 01:44 UTC is 2025-12-19 20:44 at -05:00
+        let now = Date::from_calendar_date(2025, time::Month::December, 19)
+            .unwrap()
+            .with_hms(23, 0, 0)
+            .unwrap();
+        let countdowns = |phases: &MoonPhasesResponse| -> Vec<_> {
+            phases
+                .with_countdowns(now)
+                .into_iter()
+                .map(|(_, until)| until)
+                .collect()
+        };
+        assert_eq!(countdowns(&utc), countdowns(&local));
+        assert_eq!(countdowns(&local)[2], time::Duration::minutes(164));
+        for day in [5, 12, 19, 20, 28] {
+            let at = Date::from_calendar_date(2025, time::Month::December, day)
+                .unwrap()
+                .with_hms(12, 0, 0)
+                .unwrap();
+            assert_eq!(
+                utc.estimated_illumination(at),
+                local.estimated_illumination(at),
+                "{at}"
+            );
+            let neighbors = |phases: &MoonPhasesResponse| {
+                phases
+                    .neighbors(at, &MoonPhase::New, &MoonPhase::Full)
+                    .map(|n| phases.instant(&n.target).unwrap())
+            };
+            assert_eq!(neighbors(&utc), neighbors(&local), "{at}");
+        }
+        let mut bad = local.clone();
+        bad.tz = Some(1e9);
+        assert!(bad.instant(&bad.phases[0]).is_err());
+        assert!(bad.with_countdowns(now).is_empty());
+    }
+
+    #[test]
+    fn when_in_offset() {
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        let new_moon = &phases.phases[2];
+        let central = time::UtcOffset::from_hms(-6, 0, 0).unwrap();
+        let local = new_moon.when_in_offset(central).unwrap();
+        assert_eq!(
+            (local.date(), local.hour(), local.minute()),
+            (
+                Date::from_calendar_date(2025, time::Month::December, 19).unwrap(),
+                19,
+                44
+            )
+        );
+        assert_eq!(local, new_moon.when().unwrap().assume_utc());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn one_day_args_format(
+            year in 1700u16..=2100,
+            month in 1u8..=12,
+            day in 1u8..=28,
+            lat in -90.0f32..=90.0,
+            long in -180.0f32..=180.0,
+            tz in -12.0f32..=14.0,
+        ) {
+            let args = OneDayArgs::builder()
+                .year(year)
+                .month(month)
+                .day(day)
+                .lat(lat)
+                .long(long)
+                .tz(tz)
+                .build()
+                .unwrap();
+            let date_re = regex::Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").unwrap();
+            let coords_re =
+                regex::Regex::new(r"^(-?\d{1,3}\.\d{4}),(-?\d{1,3}\.\d{4})$").unwrap();
+            let date = date_re.captures(&args.date).unwrap();
+            proptest::prop_assert_eq!(date[1].parse::<u16>().unwrap(), year);
+            proptest::prop_assert_eq!(date[2].parse::<u8>().unwrap(), month);
+            proptest::prop_assert_eq!(date[3].parse::<u8>().unwrap(), day);
+            let coords = coords_re.captures(&args.coords).unwrap();
+            proptest::prop_assert!((coords[1].parse::<f32>().unwrap() - lat).abs() < 0.0001);
+            proptest::prop_assert!((coords[2].parse::<f32>().unwrap() - long).abs() < 0.0001);
+            proptest::prop_assert_eq!(args.tz, tz);
+        }
+
+        #[test]
+        fn phase_args_format(
+            year in 1700u16..=2100,
+            month in 1u8..=12,
+            day in 1u8..=28,
+            count in 1u16..=99,
+        ) {
+            let args = PhaseArgs::build_by_date()
+                .year(year)
+                .month(month)
+                .day(day)
+                .count(count)
+                .build()
+                .unwrap();
+            let PhaseArgs::ByDate { date, nump, .. } = args else {
+                return Err(proptest::test_runner::TestCaseError::fail("expected ByDate"));
+            };
+            let date_re = regex::Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").unwrap();
+            let date = date_re.captures(&date).unwrap();
+            proptest::prop_assert_eq!(date[1].parse::<u16>().unwrap(), year);
+            proptest::prop_assert_eq!(date[2].parse::<u8>().unwrap(), month);
+            proptest::prop_assert_eq!(date[3].parse::<u8>().unwrap(), day);
+            proptest::prop_assert_eq!(nump, count);
+        }
+    }
+
+    #[tokio::test]
+    async fn one_day_id() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("id", "batch-7"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(
+                    include_str!("fixtures/one_day.json")
+                        .replace(r#""label": null"#, r#""label": "batch-7""#),
+                ),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let args = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .id("batch-7")
+            .build()
+            .unwrap();
+        let one_day = client.one_day(&args).await.unwrap();
+        assert_eq!(one_day.properties.data.label.as_deref(), Some("batch-7"));
+        let without_id = OneDayArgs::builder()
+            .year(2025)
+            .month(4)
+            .day(25)
+            .tz(0.0)
+            .lat(0.0)
+            .long(0.0)
+            .build()
+            .unwrap();
+        assert!(!query_string(&without_id).contains("id="));
+    }
+
+    #[test]
+    fn one_day_diff() {
+        let today: OneDay = serde_json::from_str(include_str!("fixtures/one_day.json")).unwrap();
+        let tomorrow: OneDay =
+            serde_json::from_str(include_str!("fixtures/one_day_next.json")).unwrap();
+        let diff = today
+            .properties
+            .data
+            .diff(&tomorrow.properties.data)
+            .unwrap();
+        let minutes = |deltas: &[(Phenomenon, time::Duration)]| {
+            deltas
+                .iter()
+                .map(|(p, d)| (*p, d.whole_minutes()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            minutes(&diff.moon.deltas),
+            [
+                (Phenomenon::Rise, 25),
+                (Phenomenon::Apex, 49),
+                (Phenomenon::Set, 72)
+            ]
+        );
+        assert_eq!(
+            minutes(&diff.sun.deltas),
+            [
+                (Phenomenon::TwilightBegins, -2),
+                (Phenomenon::Rise, -2),
+                (Phenomenon::Apex, -1),
+                (Phenomenon::Set, 1),
+                (Phenomenon::TwilightEnds, 1)
+            ]
+        );
+        assert!(diff.sun.only_in_self.is_empty() && diff.sun.only_in_other.is_empty());
+
+        let mut no_moonset = tomorrow.properties.data.clone();
+        no_moonset
+            .moon_data
+            .retain(|e| e.phenomenon != Phenomenon::Set);
+        let diff = today.properties.data.diff(&no_moonset).unwrap();
+        assert_eq!(diff.moon.deltas.len(), 2);
+        assert_eq!(diff.moon.only_in_self, [Phenomenon::Set]);
+        assert!(diff.moon.only_in_other.is_empty());
+        let diff = no_moonset.diff(&today.properties.data).unwrap();
+        assert_eq!(diff.moon.only_in_other, [Phenomenon::Set]);
+    }
+
+    #[test]
+    fn unknown_variants() {
+        let json = include_str!("fixtures/one_day.json")
+            .replace("Waning Crescent", "Balsamic")
+            .replace("Begin Civil Twilight", "Begin Nautical Twilight");
+        let parsed = serde_json::from_str::<OneDay>(&json);
+        if cfg!(feature = "strict") {
+            assert!(parsed.is_err());
+            return;
+        }
+        let data = parsed.unwrap().properties.data;
+        assert_eq!(data.current_phase, MoonPhase::Unknown("Balsamic".into()));
+        assert_eq!(
+            data.sun_data[0].phenomenon,
+            Phenomenon::Unknown("Begin Nautical Twilight".into())
+        );
+        assert_eq!(data.current_phase.as_index(), None);
+        let round_trip: OneDay = serde_json::from_str(
+            &serde_json::to_string(&OneDay {
+                properties: OneDayProps { data },
+                geometry: None,
+                from_cache: false,
+                fetched_at: None,
+                args: None,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            round_trip.properties.data.current_phase,
+            MoonPhase::Unknown("Balsamic".into())
+        );
+    }
+
+    #[test]
+    fn unknown_value() {
+        let value = UnknownValue::from("Balsamic");
+        assert_eq!(value.as_str(), "Balsamic");
+        assert_eq!(format!("{value} {value:?}"), r#"Balsamic "Balsamic""#);
+        let long = "Begin Astronomical Twilight (Nautical)";
+        assert_eq!(
+            UnknownValue::from(long).as_str(),
+            &long[..UnknownValue::CAPACITY]
+        );
+        let wide = "é".repeat(20);
+        assert_eq!(UnknownValue::from(wide.as_str()).as_str(), "é".repeat(16));
+        let phase = MoonPhase::Unknown(value);
+        let copy = phase;
+        assert_eq!(phase, copy);
+    }
+
+    #[test]
+    fn daylight_and_solar_noon() {
+        let parse = |json: &str| {
+            serde_json::from_str::<OneDay>(json)
+                .unwrap()
+                .properties
+                .data
+        };
+        let equator = parse(include_str!("fixtures/one_day_equator.json"));
+        assert_eq!(
+            equator.daylight_duration(),
+            Some(time::Duration::hours(12) + time::Duration::minutes(8))
+        );
+        assert_eq!(
+            equator.solar_noon(),
+            Some(time::Time::from_hms(12, 23, 0).unwrap())
+        );
+        let north = parse(include_str!("fixtures/one_day_high_latitude.json"));
+        assert_eq!(
+            north.daylight_duration(),
+            Some(time::Duration::hours(20) + time::Duration::minutes(46))
+        );
+        assert_eq!(
+            north.solar_noon(),
+            Some(time::Time::from_hms(13, 28, 0).unwrap())
+        );
+        for polar in [
+            include_str!("fixtures/one_day_polar_day.json"),
+            include_str!("fixtures/one_day_polar_night.json"),
+        ] {
+            let polar = parse(polar);
+            assert_eq!(polar.daylight_duration(), None);
+            assert_eq!(polar.solar_noon(), None);
+        }
+        // the same day requested in UTC, sunset falls after midnight
+        let mut utc = equator;
+        utc.sun_data[1].time = Time {
+            hour: 11,
+            minute: 19,
+            second: 0,
+        };
+        utc.sun_data[3].time = Time {
+            hour: 0,
+            minute: 27,
+            second: 0,
+        };
+        assert_eq!(
+            utc.daylight_duration(),
+            Some(time::Duration::hours(13) + time::Duration::minutes(8))
+        );
+    }
+
+    #[test]
+    fn quoted_numbers() {
+        let numeric: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        let quoted: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date_quoted.json")).unwrap();
+        assert_eq!(numeric.count, quoted.count);
+        for (lhs, rhs) in numeric.phases.iter().zip(&quoted.phases) {
+            assert_eq!(lhs.when().unwrap(), rhs.when().unwrap());
+        }
+        let one_day = include_str!("fixtures/one_day.json")
+            .replace(r#""day": 25"#, r#""day": "25""#)
+            .replace(r#""year": 2025"#, r#""year": "2025""#);
+        let one_day: OneDay = serde_json::from_str(&one_day).unwrap();
+        assert_eq!(one_day.properties.data.year, 2025);
+        assert_eq!(one_day.properties.data.closest_phase.year, 2025);
+        assert!(serde_json::from_str::<MoonPhasesResponse>(
+            r#"{"numphases": "eight", "phasedata": []}"#
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn moon_calendar() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(31)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/date"))
+            .and(matchers::query_param("date", "2025-12-01"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("fixtures/phases_by_date.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let calendar = MoonCalendar::builder(&client)
+            .year(2025)
+            .month(time::Month::December)
+            .coords(Coords::new(43.9033, -91.6401))
+            .tz(-6.0)
+            .fetch()
+            .await
+            .unwrap();
+        assert_eq!(calendar.days.len(), 31);
+        let phase_days: Vec<u8> = calendar.phases.iter().map(|p| p.day).collect();
+        assert_eq!(phase_days, [4, 11, 20, 27]);
+    }
+
+    #[tokio::test]
+    async fn error_source_chain() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"numphases\": "))
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let err = client.phases(&PhaseArgs::year(2025)).await.unwrap_err();
+        let err: &(dyn std::error::Error + 'static) = err.as_ref();
+        assert!(err.source().is_some());
+        assert!(std::iter::successors(Some(err), |e| e.source())
+            .any(|e| e.downcast_ref::<serde_json::Error>().is_some()));
+    }
+
+    #[cfg(feature = "tz-iana")]
+    #[test]
+    fn iana_offset() {
+        let date = |m, d| Date::from_calendar_date(2025, m, d).unwrap();
+        let chicago = |date| iana_offset_hours("America/Chicago", date).unwrap();
+        assert_eq!(chicago(date(time::Month::January, 15)), -6.0);
+        assert_eq!(chicago(date(time::Month::March, 9)), -5.0);
+        assert_eq!(chicago(date(time::Month::July, 4)), -5.0);
+        assert_eq!(chicago(date(time::Month::November, 2)), -6.0);
+        assert_eq!(
+            iana_offset_hours("Asia/Kolkata", date(time::Month::July, 4)).unwrap(),
+            5.5
+        );
+        assert_eq!(
+            iana_offset_hours("America/St_Johns", date(time::Month::January, 15)).unwrap(),
+            -3.5
+        );
+        assert!(iana_offset_hours("Mars/Olympus_Mons", date(time::Month::July, 4)).is_err());
+    }
+
+    #[cfg(feature = "disk-cache")]
+    #[tokio::test]
+    async fn disk_cache() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let dir = std::env::temp_dir().join(format!("moon-unit-disk-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let client = || {
+            Client::with_base_url(server.uri())
+                .unwrap()
+                .with_disk_cache(&dir)
+        };
+        let fresh = client().phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(!fresh.from_cache());
+        let cached = client().phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(cached.from_cache());
+        assert!(cached.fetched_at().unwrap() <= OffsetDateTime::now_utc());
+        assert_eq!(cached.phases.len(), fresh.phases.len());
+        std::fs::write(dir.join("year-2025.json"), "{\"count\": ").unwrap();
+        let refetched = client().phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(!refetched.from_cache());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let jan_1 = |year| Date::from_calendar_date(year, time::Month::January, 1).unwrap();
+        assert!(fresh.settled_before(jan_1(2026)));
+        assert!(!fresh.settled_before(jan_1(2025)));
+    }
+
+    #[test]
+    fn coords_from_map_url() {
+        let coords = |url| {
+            let coords = Coords::from_map_url(url).unwrap();
+            (coords.lat, coords.long)
+        };
+        for url in [
+            "https://maps.google.com/?q=43.9033,-91.6401",
+            "https://www.google.com/maps/search/?api=1&query=43.9033%2C-91.6401",
+            "https://www.google.com/maps/place/La+Crosse/@43.9033,-91.6401,15z/data=!3m1",
+            "https://maps.apple.com/?ll=43.9033,-91.6401&z=12",
+            "https://www.openstreetmap.org/?mlat=43.9033&mlon=-91.6401#map=15/43.9/-91.6",
+            "https://www.openstreetmap.org/#map=15/43.9033/-91.6401",
+            "https://www.bing.com/maps?q=La+Crosse&center=43.9033,-91.6401",
+        ] {
+            assert_eq!(coords(url), (43.9033, -91.6401), "{url}");
+        }
+        for url in [
+            "not a url",
+            "https://maps.google.com/?q=La+Crosse",
+            "https://example.com/",
+            "https://maps.google.com/?q=143.9,-91.6",
+            "https://maps.google.com/?q=43.9,-191.6",
+        ] {
+            assert!(Coords::from_map_url(url).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn coords_to_dms() {
+        assert_eq!(
+            Coords::new(43.9033, -91.6401).to_dms(),
+            "43°54'12\"N 91°38'24\"W"
+        );
+        assert_eq!(
+            Coords::new(-33.8688, 151.2093).to_dms(),
+            "33°52'8\"S 151°12'33\"E"
+        );
+        assert_eq!(Coords::new(0.0, 0.0).to_dms(), "0°0'0\"N 0°0'0\"E");
+        assert_eq!(Coords::new(-0.0, -0.0).to_dms(), "0°0'0\"N 0°0'0\"E");
+        assert_eq!(
+            Coords::new(10.99999, -179.99999).to_dms(),
+            "11°0'0\"N 180°0'0\"W"
+        );
+    }
+
+    #[cfg(feature = "localtz")]
+    #[test]
+    fn for_local_date() {
+        match OneDayArgs::for_local_date(Coords::new(43.9033, -91.6401)) {
+            Ok(args) => {
+                let offset = time::UtcOffset::current_local_offset().unwrap();
+                let today = OffsetDateTime::now_utc().to_offset(offset).date();
+                assert_eq!(args.tz, hours_from_offset(offset).unwrap());
+                assert_eq!(
+                    args.date,
+                    format!(
+                        "{:04}-{:02}-{:02}",
+                        today.year(),
+                        u8::from(today.month()),
+                        today.day()
+                    )
+                );
+            }
+            Err(e) => assert!(e.to_string().contains("local UTC offset"), "{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptors() {
+        use std::sync::{Arc, Mutex};
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_interceptor({
+                let requests = requests.clone();
+                move |request| {
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((request.method().clone(), request.url().clone()))
+                }
+            })
+            .with_response_interceptor({
+                let statuses = statuses.clone();
+                move |response| statuses.lock().unwrap().push(response.status())
+            });
+        client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0, reqwest::Method::GET);
+        assert_eq!(requests[0].1.path(), "/api/moon/phases/year");
+        assert_eq!(requests[0].1.query(), Some("year=2025"));
+        assert_eq!(*statuses.lock().unwrap(), [reqwest::StatusCode::OK]);
+    }
+
+    #[tokio::test]
+    async fn phases_years() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(6)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri()).unwrap();
+        let years: Vec<u16> = client
+            .phases_years(1900..=1905)
+            .map_ok(|(year, phases)| {
+                assert_eq!(phases.phases.len(), 49);
+                year
+            })
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(years, [1900, 1901, 1902, 1903, 1904, 1905]);
+        let mut requested: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|r| {
+                r.url
+                    .query_pairs()
+                    .find(|(k, _)| k == "year")
+                    .map(|(_, v)| v.into_owned())
+            })
+            .collect();
+        requested.sort();
+        assert_eq!(requested, ["1900", "1901", "1902", "1903", "1904", "1905"]);
+    }
+
+    #[test]
+    fn geometry() {
+        let one_day = include_str!("./fixtures/one_day.json");
+        let parsed: OneDay = serde_json::from_str(one_day).unwrap();
+        let coords = parsed.geometry.unwrap().coords().unwrap();
+        assert_eq!(coords, Coords::new(43.9033, -91.6401));
+
+        let mut value: serde_json::Value = serde_json::from_str(one_day).unwrap();
+        value.as_object_mut().unwrap().remove("geometry");
+        let parsed: OneDay = serde_json::from_value(value.clone()).unwrap();
+        assert!(parsed.geometry.is_none());
+
+        value["geometry"] = serde_json::json!({});
+        let parsed: OneDay = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.geometry.unwrap().coords(), None);
+
+        value["geometry"] = serde_json::json!({
+            "type": "Polygon",
+            "coordinates": [[[-91.7, 43.8], [-91.5, 43.8], [-91.5, 44.0], [-91.7, 43.8]]]
+        });
+        let parsed: OneDay = serde_json::from_value(value.clone()).unwrap();
+        let geometry = parsed.geometry.unwrap();
+        assert!(geometry.coordinates.is_empty());
+        assert_eq!(geometry.coords(), None);
+        assert_eq!(parsed.properties.data.sun_data.len(), 5);
+
+        value["geometry"] = serde_json::json!({
+            "type": "MultiPoint",
+            "coordinates": [[-91.7, 43.8], [-91.5, 43.8]]
+        });
+        let mut bytes = serde_json::to_vec(&value).unwrap();
+        let parsed: OneDay = simd_json::serde::from_slice(&mut bytes).unwrap();
+        assert_eq!(parsed.geometry.unwrap().coords(), None);
+
+        value["geometry"] = serde_json::json!({ "type": "Point", "coordinates": "nowhere" });
+        let parsed: OneDay = serde_json::from_value(value).unwrap();
+        assert!(parsed.geometry.unwrap().coordinates.is_empty());
+    }
+
+    #[test]
+    fn base_url_validation() {
+        for (input, expected) in [
+            ("https://aa.usno.navy.mil", "https://aa.usno.navy.mil"),
+            ("https://aa.usno.navy.mil/", "https://aa.usno.navy.mil"),
+            ("http://127.0.0.1:8080//", "http://127.0.0.1:8080"),
+            (
+                "https://mirror.example/usno/",
+                "https://mirror.example/usno",
+            ),
+        ] {
+            assert_eq!(Client::with_base_url(input).unwrap().base_url, expected);
+        }
+        for input in [
+            "",
+            "aa.usno.navy.mil",
+            "ftp://aa.usno.navy.mil",
+            "https://",
+            "https://aa.usno.navy.mil?x=1",
+            "https://aa.usno.navy.mil/#top",
+            "mailto:someone@example.com",
+        ] {
+            assert!(Client::with_base_url(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn debug_times() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        assert_eq!(
+            format!("{:?}", data.properties.data.moon_data[0]),
+            "CelestialEvent { phenomenon: Rise, time: 05:13, altitude: None, azimuth: None }"
+        );
+        let phases: MoonPhasesResponse =
+            serde_json::from_str(include_str!("fixtures/phases_by_date.json")).unwrap();
+        assert_eq!(
+            format!("{:?}", phases.phases[2]),
+            "MoonPhaseEntry { phase: New, date: 2025-12-20, time: 01:44 }"
+        );
+    }
+
+    #[test]
+    fn supported_years() {
+        let one_day = |year| {
+            OneDayArgs::builder()
+                .year(year)
+                .month(1)
+                .day(1)
+                .lat(0.0)
+                .long(0.0)
+                .tz(0.0)
+                .build()
+        };
+        let by_date = |year| {
+            PhaseArgs::build_by_date()
+                .year(year)
+                .month(1)
+                .day(1)
+                .count(4)
+                .build()
+        };
+        for year in [1700, 2100] {
+            assert!(one_day(year).is_ok(), "{year}");
+            assert!(by_date(year).is_ok(), "{year}");
+        }
+        for year in [1700, 2100] {
+            assert!(PhaseArgs::year_checked(year).is_ok(), "{year}");
+        }
+        for year in [1500, 1699, 2101, 2200] {
+            let err = one_day(year).unwrap_err().to_string();
+            assert!(err.contains("1700") && err.contains("2100"), "{err}");
+            assert!(by_date(year).is_err(), "{year}");
+            let err = PhaseArgs::year_checked(year).unwrap_err().to_string();
+            assert!(err.contains("1700") && err.contains("2100"), "{err}");
+        }
+        assert!(OneDayArgs::builder()
+            .year(1500)
+            .month(1)
+            .day(1)
+            .lat(0.0)
             .long(0.0)
-            .build())
+            .tz(0.0)
+            .supported_years(1500..=2100)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn moon_distance() {
+        let with: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_moon_distance.json")).unwrap();
+        assert_eq!(with.properties.data.moon_distance_km, Some(368_900.0));
+        assert_eq!(with.properties.data.moon_angular_diameter, Some(32.4));
+        let without: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        assert_eq!(without.properties.data.moon_distance_km, None);
+        assert_eq!(without.properties.data.moon_angular_diameter, None);
+        let json = serde_json::to_value(&without).unwrap();
+        assert!(json["properties"]["data"].get("moon_distance_km").is_none());
+    }
+
+    #[tokio::test]
+    async fn moon_phase_today() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/rstt/oneday"))
+            .and(matchers::query_param("coords", "0.0000,0.0000"))
+            .and(matchers::query_param("tz", "0.0"))
+            .and(matchers::query_param("date", "2025-04-25"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/one_day.json")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let now = Date::from_calendar_date(2025, time::Month::April, 25)
+            .unwrap()
+            .with_hms(23, 59, 0)
+            .unwrap()
+            .assume_utc();
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_clock(FixedClock(now));
+        assert_eq!(
+            client.moon_phase_today().await.unwrap(),
+            (MoonPhase::WaningCrescent, 8)
+        );
+        let data = client
+            .one_day_at(now.date(), Coords::new(0.0, 0.0), 0.0)
+            .await
+            .unwrap();
+        assert_eq!(data.fetched_at(), Some(now));
+    }
+
+    #[test]
+    fn one_day_args_from_str() {
+        let args: OneDayArgs = "2025-04-25@43.9,-91.6 tz=-5".parse().unwrap();
+        assert_eq!(args.date, "2025-04-25");
+        assert_eq!(args.coords, "43.9000,-91.6000");
+        assert_eq!(args.tz, -5.0);
+        assert_eq!(args.id, None);
+        let args = OneDayArgs::try_from("  2025-4-5@-43.9,91.6   id=home tz=5.5 ").unwrap();
+        assert_eq!(args.date, "2025-04-05");
+        assert_eq!(args.tz, 5.5);
+        assert_eq!(args.id.as_deref(), Some("home"));
+        let args: OneDayArgs = "1999-12-31@0,0".parse().unwrap();
+        assert_eq!(args.tz, 0.0);
+        for bad in [
+            "",
+            "2025-04-25",
+            "2025-04-25@43.9",
+            "2025-04@43.9,-91.6",
+            "2025-04-xx@43.9,-91.6",
+            "2025-04-25@north,-91.6",
+            "2025-04-25@43.9,-91.6 tz=five",
+            "2025-04-25@43.9,-91.6 zone=-5",
+            "2025-04-25@43.9,-91.6 -5",
+            "2200-04-25@43.9,-91.6",
+        ] {
+            assert!(bad.parse::<OneDayArgs>().is_err(), "{bad:?} should fail");
+        }
+    }
+
+    #[test]
+    fn one_day_shapes() {
+        let envelope: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let bare: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_bare.json")).unwrap();
+        assert!(envelope.geometry.is_some());
+        assert!(bare.geometry.is_none());
+        assert_eq!(
+            serde_json::to_value(&envelope.properties).unwrap(),
+            serde_json::to_value(&bare.properties).unwrap()
+        );
+        let mut bytes = include_bytes!("./fixtures/one_day_bare.json").to_vec();
+        let simd: OneDay = simd_json::serde::from_slice(&mut bytes).unwrap();
+        assert_eq!(simd.properties.data.sun_data.len(), 5);
+        let err = serde_json::from_str::<OneDay>("[]").unwrap_err();
+        assert!(err.to_string().contains("invalid length 0"), "{err}");
+        assert!(serde_json::from_str::<OneDay>("\"oneday\"").is_err());
+        let err = serde_json::from_str::<OneDay>(r#"{"type": "Feature"}"#).unwrap_err();
+        assert!(
+            err.to_string().contains("missing field `properties`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let mut data = data.properties.data;
+        assert_eq!(
+            data.summary().unwrap(),
+            "Fri Apr 25 2025 — Waning Crescent (8%), sunrise 06:07, sunset 19:57, \
+             moonrise 05:13, moonset 16:53"
+        );
+        data.moon_data
+            .retain(|event| event.phenomenon != Phenomenon::Rise);
+        data.sun_data.clear();
+        assert_eq!(
+            data.summary().unwrap(),
+            "Fri Apr 25 2025 — Waning Crescent (8%), moonset 16:53"
+        );
+        data.month = 13;
+        assert!(data.summary().is_err());
+    }
+
+    #[test]
+    fn seconds() {
+        let day: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_seconds.json")).unwrap();
+        let data = &day.properties.data;
+        assert_eq!(
+            data.sun().rise(),
+            Some(time::Time::from_hms(6, 7, 51).unwrap())
+        );
+        assert_eq!(
+            data.moon().set(),
+            Some(time::Time::from_hms(16, 53, 27).unwrap())
+        );
+        assert_eq!(
+            data.closest_phase.when().unwrap().time(),
+            time::Time::from_hms(14, 31, 9).unwrap()
+        );
+        assert_eq!(data.sun_data[1].time.to_string(), "06:07:51");
+        let round_trip: OneDay =
+            serde_json::from_str(&serde_json::to_string(&day).unwrap()).unwrap();
+        assert_eq!(round_trip.properties.data.sun().rise(), data.sun().rise());
+
+        let day: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        assert_eq!(
+            day.properties.data.sun().rise(),
+            Some(time::Time::from_hms(6, 7, 0).unwrap())
+        );
+        assert_eq!(day.properties.data.sun_data[1].time.to_string(), "06:07");
+    }
+
+    #[test]
+    fn empty_events() {
+        let data: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_empty_events.json")).unwrap();
+        let data = data.properties.data;
+        assert!(!data.has_sun_events());
+        assert!(!data.has_moon_events());
+        assert!(!data.is_polar_day());
+        assert!(!data.is_polar_night());
+        assert_eq!(data.daylight_duration(), None);
+        assert_eq!(data.solar_noon(), None);
+        assert_eq!(data.sun_transit_altitude(), None);
+        assert!(data.sun().golden_hour().is_empty());
+        assert_eq!(data.sun().civil_twilight(), None);
+        assert_eq!(data.moon().rise(), None);
+        assert!(data.events_with_geometry().is_empty());
+        assert_eq!(data.events_to_csv(), "body,phenomenon,time\n");
+        let full: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        assert!(full.properties.data.has_sun_events());
+        assert!(full.properties.data.has_moon_events());
+    }
+
+    #[test]
+    fn rough_cardinal() {
+        use CardinalDirection::*;
+        for (lat, apex) in [(43.9, South), (0.0, South), (-33.9, North)] {
+            assert_eq!(Phenomenon::Rise.rough_cardinal(lat), Some(East));
+            assert_eq!(Phenomenon::Set.rough_cardinal(lat), Some(West));
+            assert_eq!(Phenomenon::Apex.rough_cardinal(lat), Some(apex));
+            assert_eq!(Phenomenon::TwilightBegins.rough_cardinal(lat), None);
+            assert_eq!(Phenomenon::TwilightEnds.rough_cardinal(lat), None);
+            assert_eq!(Phenomenon::Unknown("x".into()).rough_cardinal(lat), None);
+        }
+    }
+
+    #[test]
+    fn merge_days() {
+        let day: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let next: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_next.json")).unwrap();
+        let timeline = OneDay::merge_days(&[next.clone(), day.clone(), day.clone()]).unwrap();
+        let expected_len = [&day, &next]
+            .iter()
+            .map(|d| d.properties.data.sun_data.len() + d.properties.data.moon_data.len())
+            .sum::<usize>();
+        assert_eq!(timeline.len(), expected_len);
+        assert!(timeline.windows(2).all(|w| w[0].when <= w[1].when));
+        let first = &timeline[0];
+        assert_eq!(
+            (first.body, &first.phenomenon),
+            (Body::Moon, &Phenomenon::Rise)
+        );
+        assert_eq!(
+            (
+                first.when.date().day(),
+                first.when.hour(),
+                first.when.minute()
+            ),
+            (25, 5, 13)
+        );
+        assert_eq!(first.when.offset().whole_hours(), -5);
+        assert_eq!(timeline.last().unwrap().when.date().day(), 26);
+        assert!(OneDay::merge_days(&[]).unwrap().is_empty());
+
+        let same_instant: OneDay =
+            serde_json::from_str(&include_str!("./fixtures/one_day.json").replace(
+                r#"{"phen": "Set", "time": "16:53"}"#,
+                r#"{"phen": "Set", "time": "05:13"}"#,
+            ))
+            .unwrap();
+        let days = [same_instant.clone(), same_instant];
+        let moon = |timeline: Vec<TimelineEvent>| {
+            timeline
+                .into_iter()
+                .filter(|e| e.body == Body::Moon)
+                .map(|e| e.phenomenon)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            moon(OneDay::merge_days(&days).unwrap()),
+            [Phenomenon::Rise, Phenomenon::Set, Phenomenon::Apex]
+        );
+        let illuminated = OneDay::merge_days_illuminated(&days).unwrap();
+        assert_eq!(
+            moon(illuminated.into_iter().map(|(e, _)| e).collect()),
+            [Phenomenon::Rise, Phenomenon::Set, Phenomenon::Apex]
+        );
+    }
+
+    #[test]
+    fn merge_days_illuminated() {
+        let waxing = include_str!("./fixtures/one_day.json")
+            .replace(r#""phase": "New Moon""#, r#""phase": "Full Moon""#)
+            .replace("Waning Crescent", "Waxing Gibbous")
+            .replace(r#""8%""#, r#""80%""#);
+        let day: OneDay = serde_json::from_str(&waxing).unwrap();
+        let timeline = OneDay::merge_days_illuminated(&[day.clone(), day]).unwrap();
+        assert_eq!(timeline.len(), 8);
+        assert!(timeline
+            .iter()
+            .all(|(event, illumination)| (event.body == Body::Moon) == illumination.is_some()));
+        let moon: Vec<f32> = timeline
+            .iter()
+            .filter_map(|(_, illumination)| *illumination)
+            .collect();
+        assert_eq!(moon.len(), 3);
+        assert!(moon.windows(2).all(|w| w[0] < w[1]), "{moon:?}");
+        assert!(moon.iter().all(|i| (80.0..=100.0).contains(i)), "{moon:?}");
+
+        let waning: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let timeline = OneDay::merge_days_illuminated(&[waning]).unwrap();
+        let moon: Vec<f32> = timeline
+            .iter()
+            .filter_map(|(_, illumination)| *illumination)
+            .collect();
+        assert!(moon.windows(2).all(|w| w[0] > w[1]), "{moon:?}");
+    }
+
+    #[test]
+    fn events_with_geometry() {
+        let one_day = include_str!("./fixtures/one_day.json");
+        let with_azimuth = one_day
+            .replace(
+                r#"{"phen": "Rise", "time": "06:07"}"#,
+                r#"{"phen": "Rise", "time": "06:07", "azimuth": 71.5}"#,
+            )
+            .replace(
+                r#"{"phen": "Set", "time": "19:57"}"#,
+                r#"{"phen": "Set", "time": "19:57", "azimuth": "-71.5"}"#,
+            )
+            .replace(
+                r#"{"phen": "Rise", "time": "05:13"}"#,
+                r#"{"phen": "Rise", "time": "05:13", "azimuth": "east"}"#,
+            );
+        let data: OneDay = serde_json::from_str(&with_azimuth).unwrap();
+        let events = data.properties.data.events_with_geometry();
+        assert_eq!(events.len(), 8);
+        let (phenomenon, when, azimuth) = &events[1];
+        assert_eq!(phenomenon, &Phenomenon::Rise);
+        assert_eq!(
+            (when.hour(), when.minute(), when.offset().whole_hours()),
+            (6, 7, -5)
+        );
+        assert_eq!(azimuth.map(|a| a.degrees()), Some(71.5));
+        assert_eq!(events[3].2.map(|a| a.degrees()), Some(288.5));
+        assert_eq!(events[5].2, None);
+        assert_eq!(events.iter().filter(|e| e.2.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn isdst() {
+        let one_day = include_str!("./fixtures/one_day.json");
+        let parse = |json: &str| {
+            serde_json::from_str::<OneDay>(json)
+                .unwrap()
+                .properties
+                .data
+                .isdst
+        };
+        assert_eq!(parse(one_day), Some(false));
+        assert_eq!(
+            parse(include_str!("./fixtures/one_day_dst.json")),
+            Some(true)
+        );
+        assert_eq!(parse(&one_day.replace(r#""isdst": false,"#, "")), None);
+        for (value, expected) in [
+            (r#""true""#, Some(true)),
+            ("0", Some(false)),
+            ("null", None),
+            (r#""maybe""#, None),
+            ("[1]", None),
+        ] {
+            let json = one_day.replace(r#""isdst": false"#, &format!(r#""isdst": {value}"#));
+            assert_eq!(parse(&json), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn sun_and_moon_data() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = data.properties.data;
+        let t = |h, m| time::Time::from_hms(h, m, 0).unwrap();
+        let sun = data.sun();
+        assert_eq!(sun.events().len(), 5);
+        assert_eq!(sun.rise(), Some(t(6, 7)));
+        assert_eq!(sun.set(), Some(t(19, 57)));
+        assert_eq!(sun.transit(), Some(t(13, 2)));
+        assert_eq!(sun.civil_twilight(), Some((t(5, 35), t(20, 30))));
+        assert_eq!(
+            sun.golden_hour(),
+            [(t(6, 7), t(7, 7)), (t(18, 57), t(19, 57))]
+        );
+        let moon = data.moon();
+        assert_eq!(moon.events().len(), 3);
+        assert_eq!(moon.rise(), Some(t(5, 13)));
+        assert_eq!(moon.set(), Some(t(16, 53)));
+        assert_eq!(moon.transit(), Some(t(10, 59)));
+
+        let polar: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day_polar_day.json")).unwrap();
+        let sun = polar.properties.data.sun();
+        assert_eq!(sun.rise(), None);
+        assert_eq!(sun.civil_twilight(), None);
+        assert!(sun.golden_hour().is_empty());
+    }
+
+    #[test]
+    fn golden_and_blue_hours() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = data.properties.data;
+        let t = |h, m| time::Time::from_hms(h, m, 0).unwrap();
+        assert_eq!(
+            data.golden_hours(),
+            [(t(6, 7), t(7, 7)), (t(18, 57), t(19, 57))]
+        );
+        assert_eq!(
+            data.golden_hours_lasting(time::Duration::minutes(40)),
+            [(t(6, 7), t(6, 47)), (t(19, 17), t(19, 57))]
+        );
+        assert_eq!(
+            data.blue_hours(),
+            [(t(5, 35), t(6, 7)), (t(19, 57), t(20, 30))]
+        );
+        for fixture in [
+            include_str!("./fixtures/one_day_polar_day.json"),
+            include_str!("./fixtures/one_day_polar_night.json"),
+        ] {
+            let polar: OneDay = serde_json::from_str(fixture).unwrap();
+            assert!(polar.properties.data.golden_hours().is_empty());
+            assert!(polar.properties.data.blue_hours().is_empty());
+        }
+    }
+
+    #[test]
+    fn illumination_delta() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let mut data = data.properties.data;
+        // waning crescent at 8% heading to a new moon
+        assert_eq!(data.illumination_delta(), Some(-8));
+        data.current_phase = MoonPhase::WaxingCrescent;
+        data.closest_phase.phase = MoonPhase::FirstQuarter;
+        data.percent_illuminated = Illumination(30);
+        assert_eq!(data.illumination_delta(), Some(20));
+        data.current_phase = MoonPhase::WaxingGibbous;
+        data.closest_phase.phase = MoonPhase::Full;
+        data.percent_illuminated = Illumination(93);
+        assert_eq!(data.illumination_delta(), Some(7));
+        data.closest_phase.phase = MoonPhase::Unknown("Balsamic".into());
+        assert_eq!(data.illumination_delta(), None);
+        // a day after full, waning at 98%
+        data.current_phase = MoonPhase::WaningGibbous;
+        data.closest_phase.phase = MoonPhase::Full;
+        data.closest_phase.day = 24;
+        data.percent_illuminated = Illumination(98);
+        assert_eq!(data.illumination_delta(), None);
+        data.closest_phase.month = 13;
+        assert_eq!(data.illumination_delta(), None);
+    }
+
+    #[test]
+    fn phase_within() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = data.properties.data;
+        // New Moon at 2025-04-27 14:31 -05:00
+        let from = Date::from_calendar_date(2025, time::Month::April, 26)
+            .unwrap()
+            .with_hms(19, 31, 0)
+            .unwrap()
+            .assume_utc();
+        assert_eq!(
+            data.phase_within(time::Duration::hours(24), from),
+            Some(MoonPhase::New)
+        );
+        assert_eq!(data.phase_within(time::Duration::hours(23), from), None);
+        assert_eq!(
+            data.phase_within(time::Duration::hours(24), from + time::Duration::days(2)),
+            None
+        );
+        assert_eq!(data.phase_within(time::Duration::MAX, from), None);
+    }
+
+    #[test]
+    fn into_sun_only() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = data.properties.data;
+        let when = data.when().unwrap();
+        let sun_data = data.sun_data.clone();
+        let sun = data.into_sun_only();
+        assert_eq!(sun.when().unwrap(), when);
+        assert_eq!(sun.sun_data.len(), 5);
+        assert_eq!(
+            sun.sun_data
+                .iter()
+                .map(|e| &e.phenomenon)
+                .collect::<Vec<_>>(),
+            sun_data.iter().map(|e| &e.phenomenon).collect::<Vec<_>>()
+        );
+        assert_eq!(sun.day_of_week, "Friday");
+    }
+
+    #[test]
+    fn requested_date_matches() {
+        let data: OneDay = serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = data.properties.data;
+        let date = |d| Date::from_calendar_date(2025, time::Month::April, d).unwrap();
+        assert!(data.requested_date_matches(date(25)));
+        assert!(!data.requested_date_matches(date(24)));
+        assert!(!data.requested_date_matches(date(26)));
+        assert!(!data.requested_date_matches(
+            Date::from_calendar_date(2024, time::Month::April, 25).unwrap()
+        ));
+    }
+
+    #[test]
+    fn when_offset() {
+        let mut data: OneDay =
+            serde_json::from_str(include_str!("./fixtures/one_day.json")).unwrap();
+        let data = &mut data.properties.data;
+        let offset = |data: &OneDayData| data.when().map(|w| w.offset().as_hms());
+        assert_eq!(offset(data).unwrap(), (-5, 0, 0));
+        data.tz = 5.5;
+        assert_eq!(offset(data).unwrap(), (5, 30, 0));
+        data.tz = -3.5;
+        assert_eq!(offset(data).unwrap(), (-3, -30, 0));
+        data.tz = -0.5;
+        assert_eq!(offset(data).unwrap(), (0, -30, 0));
+        data.tz = 30.0;
+        assert!(data.when().is_err());
+        data.tz = f32::NAN;
+        assert!(data.when().is_err());
+
+        let huge = include_str!("./fixtures/one_day.json").replace("\"tz\": -5.0", "\"tz\": 1e9");
+        let day: OneDay = serde_json::from_str(&huge).unwrap();
+        let data = &day.properties.data;
+        assert_eq!(data.tz, 1e9);
+        assert!(data.utc_offset().is_err());
+        assert!(data.when().is_err());
+        assert!(data.summary().is_err());
+        assert!(OneDay::merge_days(std::slice::from_ref(&day)).is_err());
+    }
+
+    #[test]
+    fn all_variants() {
+        let phases = MoonPhase::all();
+        assert_eq!(phases.len(), 8);
+        for (i, phase) in phases.iter().enumerate() {
+            assert_eq!(phase.as_index(), Some(i as u8));
+            assert_eq!(phases.iter().filter(|p| *p == phase).count(), 1);
+        }
+        let phenomena = Phenomenon::all();
+        assert_eq!(phenomena.len(), 5);
+        for phenomenon in phenomena {
+            assert!(!matches!(phenomenon, Phenomenon::Unknown(_)));
+            assert_eq!(phenomena.iter().filter(|p| *p == phenomenon).count(), 1);
+        }
+    }
+
+    #[test]
+    fn hash_keys() {
+        use std::collections::HashSet;
+        let phases: HashSet<MoonPhase> = MoonPhase::all().into_iter().collect();
+        assert_eq!(phases.len(), 8);
+        let phenomena: HashSet<&Phenomenon> = Phenomenon::all().iter().collect();
+        assert_eq!(phenomena.len(), Phenomenon::all().len());
+        let mut counts = HashMap::new();
+        let year: MoonPhasesResponse =
+            serde_json::from_str(include_str!("./fixtures/phases_year.json")).unwrap();
+        for entry in &year.phases {
+            *counts.entry(entry.phase).or_insert(0) += 1;
+        }
+        assert_eq!(counts.values().sum::<usize>(), 49);
+        assert_eq!(counts.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn conditional_cache() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::header("If-None-Match", "\"2025-phases\""))
+            .and(matchers::header_exists("If-Modified-Since"))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"2025-phases\"")
+                    .insert_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_conditional_cache();
+        let before = OffsetDateTime::now_utc();
+        let first = client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(!first.from_cache());
+        let second = client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(second.from_cache());
+        let fetched_at = (first.fetched_at().unwrap(), second.fetched_at().unwrap());
+        assert!(before <= fetched_at.0 && fetched_at.0 <= fetched_at.1);
+        assert!(fetched_at.1 <= OffsetDateTime::now_utc());
+        assert_eq!(first.count, second.count);
+        assert_eq!(second.phases.len(), 49);
+        client.clear_conditional_cache();
+        let third = client.phases(&PhaseArgs::year(2025)).await.unwrap();
+        assert!(!third.from_cache());
+    }
+
+    #[tokio::test]
+    async fn conditional_cache_headers() {
+        use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .and(matchers::header_exists("If-None-Match"))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/api/moon/phases/year"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"2025-phases\"")
+                    .set_body_string(include_str!("./fixtures/phases_year.json")),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+        let client = Client::with_base_url(server.uri())
+            .unwrap()
+            .with_conditional_cache();
+        let header = |name: &'static str, value: &'static str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(name, reqwest::header::HeaderValue::from_static(value));
+            headers
+        };
+        let args = PhaseArgs::year(2025);
+        let german = header("Accept-Language", "de");
+        let english = header("Accept-Language", "en");
+        let correlated = header(CORRELATION_ID_HEADER, "abc-123");
+        let from_cache = [
+            client.phases_with_headers(&args, &german).await,
+            client.phases_with_headers(&args, &english).await,
+            client.phases_with_headers(&args, &german).await,
+            client.phases(&args).await,
+            client.phases_with_headers(&args, &correlated).await,
+        ]
+        .map(|phases| phases.unwrap().from_cache());
+        assert_eq!(from_cache, [false, false, true, false, true]);
+    }
+
+    #[test]
+    fn conditional_cache_capacity() {
+        let body = |n: u8| CachedBody {
+            etag: None,
+            last_modified: None,
+            body: bytes::Bytes::from(vec![n]),
+        };
+        let mut cache = ConditionalCache::new(2);
+        cache.insert("a".into(), body(1));
+        cache.insert("b".into(), body(2));
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), body(3));
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").unwrap().body, [1].as_slice());
+        cache.insert("a".into(), body(4));
+        cache.insert("d".into(), body(5));
+        assert!(cache.get("c").is_none());
+        assert_eq!(cache.get("a").unwrap().body, [4].as_slice());
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.order.len(), 2);
+        cache.clear();
+        assert!(cache.get("a").is_none());
+        let mut none = ConditionalCache::new(0);
+        none.insert("a".into(), body(1));
+        assert!(none.get("a").is_none());
+    }
+
+    #[test]
+    fn utc_offset() {
+        let build = |h, m, s| {
+            OneDayArgs::builder()
+                .year(2025)
+                .month(4)
+                .day(25)
+                .lat(0.0)
+                .long(0.0)
+                .utc_offset(time::UtcOffset::from_hms(h, m, s).unwrap())
+                .and_then(|b| b.build())
+                .map(|args| args.tz)
+        };
+        assert_eq!(build(0, 0, 0).unwrap(), 0.0);
+        assert_eq!(build(-5, 0, 0).unwrap(), -5.0);
+        assert_eq!(build(5, 30, 0).unwrap(), 5.5);
+        assert_eq!(build(5, 45, 0).unwrap(), 5.75);
+        assert_eq!(build(-3, -30, 0).unwrap(), -3.5);
+        assert_eq!(build(0, -30, 0).unwrap(), -0.5);
+        assert!(build(1, 0, 30).is_err());
+    }
+
+    #[test]
+    fn tz_convention() {
+        let build = |tz, convention| {
+            OneDayArgs::builder()
+                .year(2025)
+                .month(4)
+                .day(25)
+                .lat(0.0)
+                .long(0.0)
+                .tz_in(tz, convention)
+                .build()
+                .unwrap()
+                .tz
+        };
+        assert_eq!(TzConvention::default(), TzConvention::Iso);
+        assert_eq!(build(-5.0, TzConvention::Iso), -5.0);
+        assert_eq!(build(5.0, TzConvention::WestPositive), -5.0);
+        assert_eq!(build(-5.5, TzConvention::WestPositive), 5.5);
+        assert!(build(0.0, TzConvention::WestPositive).is_sign_positive());
     }
 
     #[test]